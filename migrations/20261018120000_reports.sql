@@ -0,0 +1,10 @@
+-- user defined reports, built-in reports live in code and are overridden by name
+CREATE TABLE reports (
+  user_id BLOB NOT NULL,
+  name TEXT NOT NULL,
+  description TEXT NOT NULL DEFAULT '',
+  filter TEXT NOT NULL,
+  sort TEXT NOT NULL,
+  columns TEXT NOT NULL,
+  PRIMARY KEY (user_id, name)
+);
@@ -0,0 +1,7 @@
+-- per user key/value settings, keys follow .taskrc naming (urgency.due.coefficient)
+CREATE TABLE user_settings (
+  user_id BLOB NOT NULL,
+  key TEXT NOT NULL,
+  value TEXT NOT NULL,
+  PRIMARY KEY (user_id, key)
+);
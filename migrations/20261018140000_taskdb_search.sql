@@ -0,0 +1,45 @@
+-- Full-text index over taskdb_tasks, kept up to date by SqlxStorage set_task/delete_task.
+-- uuid is bound by sqlx as a BLOB like in taskdb_tasks, status is only used to filter.
+-- uuid is UNINDEXED, so rows are found by rowid through taskdb_search_rows instead of
+-- scanning the whole index on every task write.
+CREATE VIRTUAL TABLE IF NOT EXISTS taskdb_search USING fts5 (
+  uuid UNINDEXED,
+  status UNINDEXED,
+  description,
+  annotations,
+  tags,
+  project,
+  tokenize = 'porter unicode61'
+);
+
+INSERT INTO taskdb_search (uuid, status, description, annotations, tags, project)
+SELECT
+  t.uuid,
+  coalesce(json_extract (t.data, '$.status'), ''),
+  coalesce(json_extract (t.data, '$.description'), ''),
+  coalesce(
+    (
+      SELECT group_concat (value, ' ')
+      FROM json_each (t.data)
+      WHERE key LIKE 'annotation\_%' ESCAPE '\'
+    ),
+    ''
+  ),
+  coalesce(
+    (
+      SELECT group_concat (substr (key, 5), ' ')
+      FROM json_each (t.data)
+      WHERE key LIKE 'tag\_%' ESCAPE '\'
+    ),
+    ''
+  ),
+  coalesce(json_extract (t.data, '$.project'), '')
+FROM taskdb_tasks t;
+
+CREATE TABLE IF NOT EXISTS taskdb_search_rows (
+  id INTEGER PRIMARY KEY,
+  uuid BLOB NOT NULL UNIQUE
+);
+
+INSERT INTO taskdb_search_rows (id, uuid)
+SELECT rowid, uuid FROM taskdb_search;
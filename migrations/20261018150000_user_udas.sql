@@ -0,0 +1,12 @@
+-- user defined attribute schema, the values themselves live on the tasks
+CREATE TABLE user_udas (
+  user_id BLOB NOT NULL,
+  name TEXT NOT NULL,
+  type TEXT NOT NULL,
+  label TEXT NOT NULL DEFAULT '',
+  -- comma separated allowed values, empty for any
+  allowed TEXT NOT NULL DEFAULT '',
+  default_value TEXT NOT NULL DEFAULT '',
+  coefficient REAL NOT NULL DEFAULT 0,
+  PRIMARY KEY (user_id, name)
+);
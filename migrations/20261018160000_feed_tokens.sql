@@ -0,0 +1,5 @@
+-- secret calendar feed urls, calendar apps can't log in so the token is the login
+CREATE TABLE feed_tokens (
+  user_id BLOB PRIMARY KEY NOT NULL,
+  token BLOB NOT NULL UNIQUE
+);
@@ -0,0 +1,17 @@
+-- users syncing their own taskchampion account instead of the shared one,
+-- client id and secret are sealed together with TASK_CREDENTIALS_KEY, bound to
+-- the user they belong to
+CREATE TABLE task_accounts (
+  user_id BLOB PRIMARY KEY NOT NULL,
+  url TEXT NOT NULL,
+  nonce BLOB NOT NULL,
+  credentials BLOB NOT NULL
+);
+
+-- the salt TASK_CREDENTIALS_KEY is stretched with, one per install
+CREATE TABLE credentials_salt (
+  id INTEGER PRIMARY KEY CHECK (id = 1),
+  salt BLOB NOT NULL
+);
+
+INSERT INTO credentials_salt (id, salt) VALUES (1, randomblob(16));
@@ -0,0 +1,34 @@
+-- the taskdb_* tables of the main database, for the databases of users with
+-- their own taskchampion account. The replicas tests check it against the
+-- migrations one up.
+CREATE TABLE IF NOT EXISTS taskdb_tasks (uuid TEXT PRIMARY KEY, data TEXT NOT NULL);
+
+CREATE TABLE IF NOT EXISTS taskdb_operations (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  data TEXT NOT NULL,
+  synced BOOLEAN NOT NULL DEFAULT false,
+  uuid BLOB
+);
+
+CREATE INDEX IF NOT EXISTS taskdb_operations_by_uuid ON taskdb_operations (uuid);
+
+CREATE INDEX IF NOT EXISTS taskdb_operations_by_synced ON taskdb_operations (synced);
+
+CREATE TABLE IF NOT EXISTS taskdb_working_set (id INTEGER PRIMARY KEY, uuid TEXT NOT NULL);
+
+CREATE TABLE IF NOT EXISTS taskdb_sync_meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS taskdb_search USING fts5 (
+  uuid UNINDEXED,
+  status UNINDEXED,
+  description,
+  annotations,
+  tags,
+  project,
+  tokenize = 'porter unicode61'
+);
+
+CREATE TABLE IF NOT EXISTS taskdb_search_rows (
+  id INTEGER PRIMARY KEY,
+  uuid BLOB NOT NULL UNIQUE
+);
@@ -0,0 +1,62 @@
+// Drag and drop for the board, a drop posts the same form the move select does
+(function () {
+  var dragged = null;
+
+  document.body.addEventListener('dragstart', function (event) {
+    var card = event.target.closest && event.target.closest('.board-card');
+    if (!card) return;
+    dragged = card;
+    card.classList.add('dragging');
+    event.dataTransfer.effectAllowed = 'move';
+    event.dataTransfer.setData('text/plain', card.dataset.uuid);
+  });
+
+  document.body.addEventListener('dragend', function () {
+    if (dragged) dragged.classList.remove('dragging');
+    dragged = null;
+    document.querySelectorAll('.board-column.drop-target').forEach(function (el) {
+      el.classList.remove('drop-target');
+    });
+  });
+
+  function columnFor(event) {
+    var column = event.target.closest && event.target.closest('.board-column');
+    if (!dragged || !column || !column.hasAttribute('data-droppable')) return null;
+    return column;
+  }
+
+  document.body.addEventListener('dragover', function (event) {
+    var column = columnFor(event);
+    if (!column) return;
+    event.preventDefault();
+    column.classList.add('drop-target');
+  });
+
+  document.body.addEventListener('dragleave', function (event) {
+    var column = columnFor(event);
+    if (column && !column.contains(event.relatedTarget)) {
+      column.classList.remove('drop-target');
+    }
+  });
+
+  document.body.addEventListener('drop', function (event) {
+    var column = columnFor(event);
+    if (!column) return;
+    event.preventDefault();
+
+    var from = dragged.closest('.board-column');
+    if (from === column) return;
+
+    var board = document.getElementById('board');
+    htmx.ajax('POST', '/board/move', {
+      values: {
+        uuid: dragged.dataset.uuid,
+        group: board.dataset.group,
+        from: from.dataset.key,
+        to: column.dataset.key,
+      },
+      target: '#board',
+      swap: 'outerHTML',
+    });
+  });
+})();
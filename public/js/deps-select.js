@@ -124,4 +124,15 @@
       results.innerHTML = '';
     }
   });
+
+  // prefill when editing an existing task,
+  // deps outside the pending list keep their uuid so they aren't dropped on save
+  (window.__DEPS__ || []).forEach((uuid) => {
+    const task = allTasks.find((t) => t.uuid === uuid) || {
+      id: uuid.slice(0, 8),
+      uuid,
+      description: uuid,
+    };
+    addPill(task);
+  });
 })();
@@ -0,0 +1,22 @@
+// Live updates, swaps in the cards of tasks changed in another tab, by
+// another user or by a sync. The server answers with a delete swap for a
+// task that was purged or left the list the card is in.
+(function () {
+  if (!window.EventSource) return;
+  var source = new EventSource('/events');
+
+  source.addEventListener('task-changed', function (event) {
+    var all = event.data === '*';
+    var uuids = all ? [] : event.data.split(' ');
+
+    document.querySelectorAll('[data-card]').forEach(function (card) {
+      if (!all && uuids.indexOf(card.dataset.uuid) === -1) return;
+      // keep a bulk selection the user is making
+      var selected = card.querySelector('.bulk-select:checked');
+      if (selected) return;
+      htmx.ajax('GET', card.dataset.card, { target: card, swap: 'outerHTML' });
+    });
+
+    htmx.trigger(document.body, 'task-changed', { uuids: uuids });
+  });
+})();
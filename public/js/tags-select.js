@@ -55,4 +55,7 @@
     const text = e.clipboardData.getData('text');
     text.split(',').forEach(addTag);
   });
+
+  // prefill when editing an existing task
+  (window.__TAGS__ || []).forEach(addTag);
 })();
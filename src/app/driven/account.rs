@@ -0,0 +1,216 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::sync::OnceCell;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    core::{models::account::TaskAccount, ports::account::TaskAccountRepository},
+    infra::{crypto::Cipher, replicas::Replicas, sync_server::SyncBackend},
+};
+
+/// what gets sealed, the url stays readable
+#[derive(Serialize, Deserialize)]
+struct Credentials {
+    client_id: Uuid,
+    encryption_secret: String,
+}
+
+pub struct TaskAccountSqlRepo {
+    pool: SqlitePool,
+    passphrase: Option<String>,
+    /// derived from the passphrase and the install's salt on first use
+    cipher: OnceCell<Cipher>,
+    replicas: Arc<Replicas>,
+}
+
+impl TaskAccountSqlRepo {
+    async fn cipher(&self) -> Result<&Cipher> {
+        let passphrase = self
+            .passphrase
+            .clone()
+            .ok_or(anyhow!("Set TASK_CREDENTIALS_KEY to store task accounts"))?;
+        self.cipher
+            .get_or_try_init(|| async {
+                let salt = sqlx::query!("SELECT salt FROM credentials_salt WHERE id = 1")
+                    .fetch_one(&self.pool)
+                    .await?
+                    .salt;
+                // stretching the passphrase takes a while, keep it off the runtime
+                let cipher =
+                    tokio::task::spawn_blocking(move || Cipher::new(&passphrase, &salt)).await?;
+                Ok(cipher)
+            })
+            .await
+    }
+
+    async fn open(&self, user_id: Uuid, account: TaskAccount) -> Result<()> {
+        let backend = SyncBackend::Remote {
+            url: account.url,
+            client_id: account.client_id,
+            encryption_secret: account.encryption_secret.into(),
+        };
+        self.replicas.open(user_id, backend).await
+    }
+}
+
+#[async_trait]
+impl TaskAccountRepository for TaskAccountSqlRepo {
+    fn enabled(&self) -> bool {
+        self.passphrase.is_some()
+    }
+
+    async fn account(&self, user_id: Uuid) -> Result<Option<TaskAccount>> {
+        let Some(row) = sqlx::query!(
+            "SELECT url, nonce, credentials FROM task_accounts WHERE user_id = ?",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        let credentials = self
+            .cipher()
+            .await?
+            .open(&row.nonce, &row.credentials, user_id.as_bytes())?;
+        let credentials: Credentials = serde_json::from_slice(&credentials)?;
+        Ok(Some(TaskAccount {
+            url: row.url,
+            client_id: credentials.client_id,
+            encryption_secret: credentials.encryption_secret,
+        }))
+    }
+
+    async fn set_account(&self, user_id: Uuid, account: TaskAccount) -> Result<()> {
+        let credentials = serde_json::to_vec(&Credentials {
+            client_id: account.client_id,
+            encryption_secret: account.encryption_secret.clone(),
+        })?;
+        let (nonce, credentials) = self
+            .cipher()
+            .await?
+            .seal(&credentials, user_id.as_bytes())?;
+        sqlx::query!(
+            r#"
+                INSERT INTO task_accounts (user_id, url, nonce, credentials)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT (user_id) DO UPDATE SET
+                    url = excluded.url,
+                    nonce = excluded.nonce,
+                    credentials = excluded.credentials
+            "#,
+            user_id,
+            account.url,
+            nonce,
+            credentials,
+        )
+        .execute(&self.pool)
+        .await?;
+
+        self.open(user_id, account).await
+    }
+
+    async fn remove_account(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query!("DELETE FROM task_accounts WHERE user_id = ?", user_id)
+            .execute(&self.pool)
+            .await?;
+        self.replicas.close(user_id);
+        Ok(())
+    }
+
+    async fn open_all(&self) -> Result<usize> {
+        if !self.enabled() {
+            return Ok(0);
+        }
+        let users = sqlx::query!("SELECT user_id as `user_id:uuid::Uuid` FROM task_accounts")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut opened = 0;
+        for user_id in users.into_iter().map(|row| row.user_id) {
+            // one bad account shouldn't keep everyone else's tasks from loading
+            let res = match self.account(user_id).await {
+                Ok(Some(account)) => self.open(user_id, account).await,
+                Ok(None) => continue,
+                Err(err) => Err(err),
+            };
+            match res {
+                Ok(()) => opened += 1,
+                Err(err) => info!("could not open the replica of {user_id}: {err:?}"),
+            }
+        }
+        Ok(opened)
+    }
+}
+
+pub fn create_task_account_repo(
+    pool: &SqlitePool,
+    replicas: Arc<Replicas>,
+) -> Arc<TaskAccountSqlRepo> {
+    Arc::new(TaskAccountSqlRepo {
+        pool: pool.clone(),
+        passphrase: Cipher::passphrase_from_env(),
+        cipher: OnceCell::new(),
+        replicas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tokio_util::sync::CancellationToken;
+
+    #[tokio::test]
+    async fn credentials_only_open_for_their_owner() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+        let data_dir = std::env::temp_dir().join(format!("taskbane-accounts-{}", Uuid::new_v4()));
+        let replicas = Replicas::with_backend(
+            &pool,
+            SyncBackend::None,
+            data_dir.clone(),
+            CancellationToken::new(),
+        );
+        let repo = TaskAccountSqlRepo {
+            pool: pool.clone(),
+            passphrase: Some("correct horse".to_owned()),
+            cipher: OnceCell::new(),
+            replicas,
+        };
+
+        let (alice, mallory) = (Uuid::new_v4(), Uuid::new_v4());
+        let account = TaskAccount::new(
+            "https://tasks.example.com",
+            "c3d0a5b1-2f5e-4a53-9a3b-f0e8f4a1b2c3",
+            "s3cret",
+        )
+        .unwrap();
+        repo.set_account(alice, account.clone()).await.unwrap();
+        assert_eq!(repo.account(alice).await.unwrap(), Some(account));
+
+        // alice's sealed credentials copied into a row of mallory's own
+        sqlx::query(
+            "INSERT INTO task_accounts (user_id, url, nonce, credentials) \
+             SELECT ?, url, nonce, credentials FROM task_accounts WHERE user_id = ?",
+        )
+        .bind(mallory)
+        .bind(alice)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(repo.account(mallory).await.is_err());
+
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+}
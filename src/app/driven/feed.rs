@@ -0,0 +1,67 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::core::ports::feed::FeedRepository;
+
+pub struct FeedSqlRepo {
+    pool: SqlitePool,
+}
+
+#[async_trait]
+impl FeedRepository for FeedSqlRepo {
+    async fn token(&self, user_id: Uuid) -> Result<Option<Uuid>> {
+        let token = sqlx::query!(
+            "SELECT token as `token:uuid::Uuid` FROM feed_tokens WHERE user_id = ?",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|row| row.token);
+
+        Ok(token)
+    }
+
+    async fn user(&self, token: Uuid) -> Result<Option<Uuid>> {
+        let user_id = sqlx::query!(
+            "SELECT user_id as `user_id:uuid::Uuid` FROM feed_tokens WHERE token = ?",
+            token
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(|row| row.user_id);
+
+        Ok(user_id)
+    }
+
+    async fn set_token(&self, user_id: Uuid, token: Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO feed_tokens (user_id, token)
+                VALUES (?, ?)
+                ON CONFLICT (user_id) DO UPDATE SET token = excluded.token
+            "#,
+            user_id,
+            token,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
+    async fn revoke(&self, user_id: Uuid) -> Result<()> {
+        sqlx::query!("DELETE FROM feed_tokens WHERE user_id = ?", user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(Error::from)
+            .map(|_| ())
+    }
+}
+
+pub fn create_feed_repo(pool: &SqlitePool) -> Arc<FeedSqlRepo> {
+    Arc::new(FeedSqlRepo { pool: pool.clone() })
+}
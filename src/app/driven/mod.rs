@@ -1,25 +1,44 @@
+mod account;
 mod auth;
+mod feed;
+mod report;
+mod search;
+mod settings;
+mod sync;
 mod task;
+mod uda;
 mod user;
 
 use std::sync::Arc;
 
 use sqlx::SqlitePool;
-use taskchampion::storage::Storage;
 
-use crate::{core::ports, infra::task::ArcRep};
+use crate::{core::ports, infra::replicas::Replicas};
 
-pub fn create_driven<S: Storage + Sync + 'static>(
-    pool: &SqlitePool,
-    task_storage: ArcRep<S>,
-) -> (
-    Arc<dyn ports::user::UserRepository>,
-    Arc<dyn ports::auth::AuthRepository>,
-    Arc<dyn ports::task::TaskRepository>,
-) {
-    (
-        user::create_user_repo(pool),
-        auth::create_auth_repo(pool),
-        task::create_task_repo(task_storage),
-    )
+pub struct Repos {
+    pub user_repo: Arc<dyn ports::user::UserRepository>,
+    pub auth_repo: Arc<dyn ports::auth::AuthRepository>,
+    pub task_repo: Arc<dyn ports::task::TaskRepository>,
+    pub report_repo: Arc<dyn ports::report::ReportRepository>,
+    pub settings_repo: Arc<dyn ports::settings::SettingsRepository>,
+    pub search_repo: Arc<dyn ports::search::TaskSearchRepository>,
+    pub uda_repo: Arc<dyn ports::uda::UdaRepository>,
+    pub feed_repo: Arc<dyn ports::feed::FeedRepository>,
+    pub sync_repo: Arc<dyn ports::sync::SyncRepository>,
+    pub account_repo: Arc<dyn ports::account::TaskAccountRepository>,
+}
+
+pub fn create_driven(pool: &SqlitePool, replicas: Arc<Replicas>) -> Repos {
+    Repos {
+        user_repo: user::create_user_repo(pool),
+        auth_repo: auth::create_auth_repo(pool),
+        task_repo: task::create_task_repo(replicas.clone()),
+        report_repo: report::create_report_repo(pool),
+        settings_repo: settings::create_settings_repo(pool),
+        search_repo: search::create_search_repo(replicas.clone()),
+        uda_repo: uda::create_uda_repo(pool),
+        feed_repo: feed::create_feed_repo(pool),
+        sync_repo: sync::create_sync_repo(replicas.clone()),
+        account_repo: account::create_task_account_repo(pool, replicas),
+    }
 }
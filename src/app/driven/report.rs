@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::core::{models::report::Report, ports::report::ReportRepository};
+
+pub struct ReportSqlRepo {
+    pool: SqlitePool,
+}
+
+struct ReportRow {
+    name: String,
+    description: String,
+    filter: String,
+    sort: String,
+    columns: String,
+}
+
+impl TryFrom<ReportRow> for Report {
+    type Error = Error;
+
+    fn try_from(row: ReportRow) -> Result<Self> {
+        Ok(Report::new(
+            row.name,
+            row.description,
+            row.filter,
+            row.sort.parse()?,
+            row.columns.parse()?,
+            false,
+        ))
+    }
+}
+
+#[async_trait]
+impl ReportRepository for ReportSqlRepo {
+    async fn list(&self, user_id: Uuid) -> Result<Vec<Report>> {
+        sqlx::query_as!(
+            ReportRow,
+            r#"
+                SELECT name, description, filter, sort, columns
+                FROM reports
+                WHERE user_id = ?
+                ORDER BY name
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(Report::try_from)
+        .collect()
+    }
+
+    async fn get(&self, user_id: Uuid, name: &str) -> Result<Option<Report>> {
+        sqlx::query_as!(
+            ReportRow,
+            r#"
+                SELECT name, description, filter, sort, columns
+                FROM reports
+                WHERE user_id = ? AND name = ?
+            "#,
+            user_id,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Report::try_from)
+        .transpose()
+    }
+
+    async fn save(&self, user_id: Uuid, report: &Report) -> Result<()> {
+        let sort = report.sort.to_string();
+        let columns = report.columns.to_string();
+        sqlx::query!(
+            r#"
+                INSERT INTO reports (user_id, name, description, filter, sort, columns)
+                VALUES (?, ?, ?, ?, ?, ?)
+                ON CONFLICT (user_id, name) DO UPDATE SET
+                    description = excluded.description,
+                    filter = excluded.filter,
+                    sort = excluded.sort,
+                    columns = excluded.columns
+            "#,
+            user_id,
+            report.name,
+            report.description,
+            report.filter,
+            sort,
+            columns,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
+    async fn delete(&self, user_id: Uuid, name: &str) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM reports WHERE user_id = ? AND name = ?",
+            user_id,
+            name
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+}
+
+pub fn create_report_repo(pool: &SqlitePool) -> Arc<ReportSqlRepo> {
+    Arc::new(ReportSqlRepo { pool: pool.clone() })
+}
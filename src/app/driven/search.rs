@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use itertools::Itertools;
+use uuid::Uuid;
+
+use crate::{core::ports::search::TaskSearchRepository, infra::replicas::Replicas};
+
+/// The full-text index lives with the tasks, in the database of the user's replica
+pub struct TaskSearchSqlRepo {
+    replicas: Arc<Replicas>,
+}
+
+/// free text as an fts5 query, every word has to match as a prefix,
+/// quoted so fts5 operators typed by the user are searched for literally
+fn fts_query(input: &str) -> Option<String> {
+    let query = input
+        .split_whitespace()
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .join(" ");
+    (!query.is_empty()).then_some(query)
+}
+
+#[async_trait]
+impl TaskSearchRepository for TaskSearchSqlRepo {
+    async fn search(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_completed: bool,
+        limit: u32,
+    ) -> Result<Vec<Uuid>> {
+        let Some(query) = fts_query(query) else {
+            return Ok(vec![]);
+        };
+
+        // description weighs most, then tags and project, then annotations
+        let uuids = sqlx::query!(
+            r#"
+                SELECT uuid as "uuid!: uuid::Uuid"
+                FROM taskdb_search
+                WHERE taskdb_search MATCH ?
+                    AND (status = 'pending' OR (? AND status = 'completed'))
+                ORDER BY bm25(taskdb_search, 0.0, 0.0, 10.0, 2.0, 4.0, 4.0)
+                LIMIT ?
+            "#,
+            query,
+            include_completed,
+            limit,
+        )
+        .fetch_all(&self.replicas.get(user_id).pool)
+        .await?
+        .into_iter()
+        .map(|row| row.uuid)
+        .collect();
+
+        Ok(uuids)
+    }
+}
+
+pub fn create_search_repo(replicas: Arc<Replicas>) -> Arc<TaskSearchSqlRepo> {
+    Arc::new(TaskSearchSqlRepo { replicas })
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::core::ports::settings::SettingsRepository;
+
+pub struct SettingsSqlRepo {
+    pool: SqlitePool,
+}
+
+#[async_trait]
+impl SettingsRepository for SettingsSqlRepo {
+    async fn list(&self, user_id: Uuid) -> Result<Vec<(String, String)>> {
+        let settings = sqlx::query!(
+            "SELECT key, value FROM user_settings WHERE user_id = ? ORDER BY key",
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.key, row.value))
+        .collect();
+
+        Ok(settings)
+    }
+
+    async fn set(&self, user_id: Uuid, key: &str, value: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO user_settings (user_id, key, value)
+                VALUES (?, ?, ?)
+                ON CONFLICT (user_id, key) DO UPDATE SET value = excluded.value
+            "#,
+            user_id,
+            key,
+            value,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
+    async fn delete(&self, user_id: Uuid, key: &str) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM user_settings WHERE user_id = ? AND key = ?",
+            user_id,
+            key
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+}
+
+pub fn create_settings_repo(pool: &SqlitePool) -> Arc<SettingsSqlRepo> {
+    Arc::new(SettingsSqlRepo { pool: pool.clone() })
+}
@@ -0,0 +1,63 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{
+    core::{models::sync::SyncStatus, ports::sync::SyncRepository},
+    infra::{replicas::Replicas, task::SyncRecord},
+};
+
+/// a manual sync answers once done, or with "syncing" when the server is slow
+const SYNC_NOW_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct SyncRepo {
+    replicas: Arc<Replicas>,
+}
+
+#[async_trait]
+impl SyncRepository for SyncRepo {
+    async fn status(&self, user_id: Uuid) -> Result<SyncStatus> {
+        let tenant = self.replicas.get(user_id);
+        with_counts(&tenant.pool, tenant.monitor.record()).await
+    }
+
+    async fn sync_now(&self, user_id: Uuid) -> Result<SyncStatus> {
+        let tenant = self.replicas.get(user_id);
+        let record = tenant.monitor.sync_now(SYNC_NOW_TIMEOUT).await;
+        with_counts(&tenant.pool, record).await
+    }
+}
+
+/// read straight from the task db, the replica is locked for the whole
+/// of a sync and the navbar shouldn't wait on the network
+async fn with_counts(pool: &SqlitePool, record: SyncRecord) -> Result<SyncStatus> {
+    let unsynced =
+        sqlx::query!("SELECT count(*) as count FROM taskdb_operations WHERE NOT synced")
+            .fetch_one(pool)
+            .await?
+            .count as usize;
+    let base_version =
+        sqlx::query!("SELECT value FROM taskdb_sync_meta WHERE key = 'base_version'")
+            .fetch_optional(pool)
+            .await?
+            .and_then(|row| Uuid::parse_str(&row.value).ok())
+            .filter(|version| !version.is_nil());
+
+    Ok(SyncStatus {
+        last_attempt: record.last_attempt,
+        last_success: record.last_success,
+        last_error: record.last_error,
+        retry_at: record.retry_at,
+        syncing: record.syncing,
+        local_only: record.local_only,
+        unsynced,
+        base_version,
+    })
+}
+
+pub fn create_sync_repo(replicas: Arc<Replicas>) -> Arc<SyncRepo> {
+    Arc::new(SyncRepo { replicas })
+}
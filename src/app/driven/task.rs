@@ -3,30 +3,110 @@ use std::sync::Arc;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use derive_more::Constructor;
-use taskchampion::{storage::Storage, Annotation, Operations, Status, Task};
+use itertools::Itertools;
+use taskchampion::{
+    chrono::{DateTime, Utc},
+    Annotation, Operation, Operations, Replica, Status, Tag, Task,
+};
+use tokio::sync::broadcast;
+use tracing::info;
 use uuid::Uuid;
 
 use crate::{
-    core::ports::task::{CreateTaskInput, TaskRepository},
-    infra::task::ArcRep,
+    core::{
+        models::recurrence::{Recurrence, RecurrenceInstance},
+        ports::task::{BulkAction, CreateTaskInput, TaskRepository},
+    },
+    infra::{
+        replicas::Replicas,
+        task::{SqlxStorage, SyncMonitor},
+    },
 };
 
+/// commits `ops` behind an undo point, so an undo reverses one action at a time,
+/// and tells `sync` so the change syncs soon
+async fn commit_step(
+    rep: &mut Replica<SqlxStorage>,
+    sync: &SyncMonitor,
+    ops: Operations,
+) -> Result<()> {
+    let step: Operations = std::iter::once(Operation::UndoPoint).chain(ops).collect();
+    let uuids = touched(&step);
+    rep.commit_operations(step).await?;
+    sync.local_change(uuids);
+    Ok(())
+}
+
+/// Tasks in the replica of the user asking
 #[derive(Constructor, Clone)]
-pub struct TaskRepo<S: Storage + Sync> {
-    replica: ArcRep<S>,
+pub struct TaskRepo {
+    replicas: Arc<Replicas>,
+}
+
+impl TaskRepo {
+    /// every task passing `filter`, tasks outside the working set get id 0
+    async fn list_matching(
+        &self,
+        user_id: Uuid,
+        filter: impl Fn(&Task) -> bool + Send,
+    ) -> Result<Vec<(usize, Task, Vec<usize>)>> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let ws = rep.working_set().await?;
+        let tasks = rep
+            .all_tasks()
+            .await?
+            .into_values()
+            .filter(filter)
+            .map(|task| {
+                let deps = task
+                    .get_dependencies()
+                    .filter_map(|uuid| ws.by_uuid(uuid))
+                    .collect();
+                let id = ws.by_uuid(task.get_uuid()).unwrap_or_default();
+                (id, task, deps)
+            })
+            .collect();
+
+        Ok(tasks)
+    }
+
+    /// moves a task in status `from` back to pending
+    async fn back_to_pending(&self, user_id: Uuid, uuid: Uuid, from: Status) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let mut ops = Operations::new();
+        let mut task = rep.get_task(uuid).await?.ok_or(anyhow!("No task found"))?;
+
+        if task.get_status() != from {
+            return Err(anyhow!("Task is not {from:?}"));
+        }
+        task.set_status(Status::Pending, &mut ops)?;
+
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
-impl<S: Storage + Sync> TaskRepository for TaskRepo<S> {
-    async fn get_task(&self, uuid: Uuid) -> Result<Option<Task>> {
-        let mut rep = self.replica.write().await;
+impl TaskRepository for TaskRepo {
+    async fn get_task(&self, user_id: Uuid, uuid: Uuid) -> Result<Option<Task>> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
         let task = rep.get_task(uuid).await?;
 
         Ok(task)
     }
 
-    async fn get_task_meta(&self, uuid: Uuid, deps: Vec<Uuid>) -> Result<(usize, Vec<usize>)> {
-        let mut rep = self.replica.write().await;
+    async fn get_task_meta(
+        &self,
+        user_id: Uuid,
+        uuid: Uuid,
+        deps: Vec<Uuid>,
+    ) -> Result<(usize, Vec<usize>)> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
         let ws = rep.working_set().await?;
         let id = ws.by_uuid(uuid).ok_or(anyhow!("No ws_id found for uuid"))?;
         let deps = deps
@@ -37,8 +117,9 @@ impl<S: Storage + Sync> TaskRepository for TaskRepo<S> {
         Ok((id, deps))
     }
 
-    async fn list(&self) -> Result<Vec<(usize, Task, Vec<usize>)>> {
-        let mut rep = self.replica.write().await;
+    async fn list(&self, user_id: Uuid) -> Result<Vec<(usize, Task, Vec<usize>)>> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
         let ws = rep.working_set().await?;
         let tasks = rep.pending_tasks().await.map(|tasks| {
             tasks
@@ -56,38 +137,131 @@ impl<S: Storage + Sync> TaskRepository for TaskRepo<S> {
 
         Ok(tasks)
     }
+    async fn list_all(&self, user_id: Uuid) -> Result<Vec<(usize, Task, Vec<usize>)>> {
+        self.list_matching(user_id, |_| true).await
+    }
+
+    async fn list_with_status(
+        &self,
+        user_id: Uuid,
+        status: Status,
+    ) -> Result<Vec<(usize, Task, Vec<usize>)>> {
+        self.list_matching(user_id, |task| task.get_status() == status).await
+    }
+
     async fn find(
         &self,
+        user_id: Uuid,
         filter: &(dyn for<'a> Fn(&'a Task) -> bool + Send + Sync),
     ) -> Result<Option<Task>> {
-        let mut rep = self.replica.write().await;
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
         let res = rep.pending_tasks().await?.into_iter().find(filter);
 
         Ok(res)
     }
 
-    async fn mark_task_done(&self, uuid: Uuid) -> Result<()> {
-        let mut rep = self.replica.write().await;
+    async fn mark_task_done(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
         let mut ops = Operations::new();
         let mut task = rep.get_task(uuid).await?.ok_or(anyhow!("No task found"))?;
 
+        if task.is_active() {
+            task.stop(&mut ops)?;
+        }
         task.done(&mut ops)?;
 
-        rep.commit_operations(ops).await?;
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
+
+        Ok(())
+    }
+
+    async fn start_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let mut ops = Operations::new();
+        let mut task = rep.get_task(uuid).await?.ok_or(anyhow!("No task found"))?;
+
+        task.start(&mut ops)?;
+
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
 
         Ok(())
     }
 
-    async fn create_task(&self, input: CreateTaskInput) -> Result<usize> {
-        let mut rep = self.replica.write().await;
+    async fn stop_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let mut ops = Operations::new();
+        let mut task = rep.get_task(uuid).await?.ok_or(anyhow!("No task found"))?;
+
+        task.stop(&mut ops)?;
+
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
+
+        Ok(())
+    }
+
+    async fn delete_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let mut ops = Operations::new();
+        let mut task = rep.get_task(uuid).await?.ok_or(anyhow!("No task found"))?;
+
+        if task.is_active() {
+            task.stop(&mut ops)?;
+        }
+        task.set_status(Status::Deleted, &mut ops)?;
+
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
+
+        Ok(())
+    }
+
+    async fn restore_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        self.back_to_pending(user_id, uuid, Status::Deleted).await
+    }
+
+    async fn reopen_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        self.back_to_pending(user_id, uuid, Status::Completed).await
+    }
+
+    async fn create_task(&self, user_id: Uuid, input: CreateTaskInput) -> Result<usize> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
         let mut ops = Operations::new();
         let uuid = Uuid::new_v4();
         let mut task = rep.create_task(uuid, &mut ops).await?;
 
-        task.set_status(Status::Pending, &mut ops)?;
+        match input.recur {
+            Some(recur) => {
+                task.set_status(Status::Recurring, &mut ops)?;
+                task.set_value("recur", Some(recur), &mut ops)?;
+                task.set_value("rtype", Some("periodic".to_owned()), &mut ops)?;
+            }
+            None => task.set_status(Status::Pending, &mut ops)?,
+        }
         task.set_due(input.due, &mut ops)?;
+        if let Some(wait) = input.wait {
+            task.set_wait(Some(wait), &mut ops)?;
+        }
+        if let Some(scheduled) = input.scheduled {
+            task.set_timestamp("scheduled", Some(scheduled), &mut ops)?;
+        }
+        if let Some(until) = input.until {
+            task.set_timestamp("until", Some(until), &mut ops)?;
+        }
         task.set_description(input.description, &mut ops)?;
         task.set_priority(input.priority, &mut ops)?;
+        if let Some(project) = input.project {
+            task.set_user_defined_attribute("project", project, &mut ops)?;
+        }
+        for (name, value) in input.udas {
+            if let Some(value) = value {
+                task.set_user_defined_attribute(name, value, &mut ops)?;
+            }
+        }
 
         for dep in input.deps.into_iter() {
             task.add_dependency(dep, &mut ops)?;
@@ -97,7 +271,7 @@ impl<S: Storage + Sync> TaskRepository for TaskRepo<S> {
             task.add_tag(tag, &mut ops)?;
         }
 
-        rep.commit_operations(ops).await?;
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
 
         let ws = rep.working_set().await?;
 
@@ -106,19 +280,368 @@ impl<S: Storage + Sync> TaskRepository for TaskRepo<S> {
         Ok(id)
     }
 
-    async fn annotate(&self, uuid: Uuid, annotation: Annotation) -> Result<()> {
-        let mut rep = self.replica.write().await;
+    async fn update_task(&self, user_id: Uuid, uuid: Uuid, input: CreateTaskInput) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let mut ops = Operations::new();
+        let mut task = rep.get_task(uuid).await?.ok_or(anyhow!("No task found"))?;
+
+        // only emit operations for fields that changed,
+        // so merges with other replicas stay clean
+        if task.get_description() != input.description {
+            task.set_description(input.description, &mut ops)?;
+        }
+        if !task.get_priority().eq_ignore_ascii_case(&input.priority) {
+            task.set_priority(input.priority, &mut ops)?;
+        }
+        if task.get_due() != input.due {
+            task.set_due(input.due, &mut ops)?;
+        }
+        if task.get_wait() != input.wait {
+            task.set_wait(input.wait, &mut ops)?;
+        }
+        if task.get_timestamp("scheduled") != input.scheduled {
+            task.set_timestamp("scheduled", input.scheduled, &mut ops)?;
+        }
+        if task.get_timestamp("until") != input.until {
+            task.set_timestamp("until", input.until, &mut ops)?;
+        }
+        // only templates take a recur value, children keep the one they were made with
+        if let Some(recur) = input.recur.filter(|recur| Some(recur.as_str()) != task.get_value("recur")) {
+            task.set_value("recur", Some(recur), &mut ops)?;
+        }
+        if task.get_user_defined_attribute("project") != input.project.as_deref() {
+            match input.project {
+                Some(project) => task.set_user_defined_attribute("project", project, &mut ops)?,
+                None => task.remove_user_defined_attribute("project", &mut ops)?,
+            }
+        }
+        for (name, value) in input.udas {
+            if task.get_user_defined_attribute(&name) == value.as_deref() {
+                continue;
+            }
+            match value {
+                Some(value) => task.set_user_defined_attribute(name, value, &mut ops)?,
+                None => task.remove_user_defined_attribute(name, &mut ops)?,
+            }
+        }
+
+        let tags = task
+            .get_tags()
+            .filter(|tag| tag.is_user())
+            .collect::<Vec<Tag>>();
+        for tag in tags.iter().filter(|tag| !input.tags.contains(tag)) {
+            task.remove_tag(tag, &mut ops)?;
+        }
+        for tag in input.tags.iter().filter(|tag| !tags.contains(tag)) {
+            task.add_tag(tag, &mut ops)?;
+        }
+
+        let deps = task.get_dependencies().collect::<Vec<Uuid>>();
+        for dep in deps.iter().filter(|dep| !input.deps.contains(dep)) {
+            task.remove_dependency(*dep, &mut ops)?;
+        }
+        for dep in input.deps.iter().filter(|dep| !deps.contains(dep)) {
+            task.add_dependency(*dep, &mut ops)?;
+        }
+
+        if ops.is_empty() {
+            return Ok(());
+        }
+
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
+
+        Ok(())
+    }
+
+    async fn annotate(&self, user_id: Uuid, uuid: Uuid, annotation: Annotation) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
         let mut ops = Operations::new();
         let mut task = rep.get_task(uuid).await?.ok_or(anyhow!("No task found"))?;
 
         task.add_annotation(annotation, &mut ops)?;
 
-        rep.commit_operations(ops).await?;
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
 
         Ok(())
     }
+
+    async fn create_recurrences(
+        &self,
+        user_id: Uuid,
+        template: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<usize> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let mut ops = Operations::new();
+        let mut parent = rep
+            .get_task(template)
+            .await?
+            .ok_or(anyhow!("No task found"))?;
+        let recurrence = match Recurrence::of_template(&parent) {
+            Ok(Some(recurrence)) => recurrence,
+            Ok(None) => return Ok(0),
+            Err(err) => {
+                info!("Skipping recurring task {template}: {err}");
+                return Ok(0);
+            }
+        };
+        let mut mask = parent.get_value("mask").unwrap_or_default().to_owned();
+        let generated = mask.chars().count();
+        let instances: Vec<RecurrenceInstance> = recurrence
+            .pending_instances(generated, now)
+            .into_iter()
+            .filter(|instance| instance.imask >= generated)
+            .collect();
+        if instances.is_empty() {
+            return Ok(0);
+        }
+
+        let data = rep
+            .get_task_data(template)
+            .await?
+            .ok_or(anyhow!("No task found"))?;
+
+        // children get everything but the template's own bookkeeping and dates
+        let inherited: Vec<(String, String)> = data
+            .iter()
+            .filter(|(key, _)| {
+                !matches!(
+                    key.as_str(),
+                    "status" | "mask" | "entry" | "modified" | "start" | "end" | "due" | "wait"
+                        | "scheduled"
+                )
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        let created = instances.len();
+        for instance in instances {
+            let mut child = rep.create_task(Uuid::new_v4(), &mut ops).await?;
+            for (key, value) in inherited.iter() {
+                child.set_value(key.as_str(), Some(value.clone()), &mut ops)?;
+            }
+            child.set_status(Status::Pending, &mut ops)?;
+            child.set_entry(Some(Utc::now()), &mut ops)?;
+            child.set_value("parent", Some(template.to_string()), &mut ops)?;
+            child.set_value("imask", Some(instance.imask.to_string()), &mut ops)?;
+            child.set_due(Some(instance.due), &mut ops)?;
+            if let Some(wait) = instance.wait {
+                child.set_wait(Some(wait), &mut ops)?;
+            }
+            if let Some(scheduled) = instance.scheduled {
+                child.set_timestamp("scheduled", Some(scheduled), &mut ops)?;
+            }
+            // the mask has a slot for every child, even if one came in out of order
+            while mask.chars().count() <= instance.imask {
+                mask.push('-');
+            }
+        }
+        parent.set_value("mask", Some(mask), &mut ops)?;
+
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
+
+        Ok(created)
+    }
+
+    async fn bulk_update(
+        &self,
+        user_id: Uuid,
+        uuids: &[Uuid],
+        actions: &[BulkAction],
+    ) -> Result<()> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let mut ops = Operations::new();
+
+        for uuid in uuids {
+            let mut task = rep
+                .get_task(*uuid)
+                .await?
+                .ok_or(anyhow!("No task found for {uuid}"))?;
+            for action in actions {
+                match action {
+                    BulkAction::Done => {
+                        if task.is_active() {
+                            task.stop(&mut ops)?;
+                        }
+                        task.done(&mut ops)?
+                    }
+                    BulkAction::Delete => {
+                        if task.is_active() {
+                            task.stop(&mut ops)?;
+                        }
+                        task.set_status(Status::Deleted, &mut ops)?;
+                    }
+                    BulkAction::Start => task.start(&mut ops)?,
+                    BulkAction::Stop => task.stop(&mut ops)?,
+                    BulkAction::Reopen => task.set_status(Status::Pending, &mut ops)?,
+                    BulkAction::Wait(wait) => task.set_wait(*wait, &mut ops)?,
+                    BulkAction::AddTag(tag) => task.add_tag(tag, &mut ops)?,
+                    BulkAction::RemoveTag(tag) => task.remove_tag(tag, &mut ops)?,
+                    BulkAction::Priority(priority) => {
+                        task.set_priority(priority.clone(), &mut ops)?
+                    }
+                    BulkAction::Due(due) => task.set_due(*due, &mut ops)?,
+                    BulkAction::Project(Some(project)) => {
+                        task.set_user_defined_attribute("project", project.clone(), &mut ops)?
+                    }
+                    BulkAction::Project(None) => {
+                        task.remove_user_defined_attribute("project", &mut ops)?
+                    }
+                }
+            }
+        }
+
+        commit_step(&mut rep, &tenant.monitor, ops).await?;
+
+        Ok(())
+    }
+
+    async fn task_operations(&self, user_id: Uuid, uuid: Uuid) -> Result<Vec<Operation>> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        Ok(rep.get_task_operations(uuid).await?)
+    }
+
+    async fn undo_operations(&self, user_id: Uuid) -> Result<Vec<Operation>> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        Ok(rep.get_undo_operations().await?)
+    }
+
+    async fn undo(&self, user_id: Uuid, operations: Vec<Operation>) -> Result<bool> {
+        let tenant = self.replicas.get(user_id);
+        let mut rep = tenant.replica.write().await;
+        let uuids = touched(&operations);
+        let undone = rep.commit_reversed_operations(operations).await?;
+        if undone {
+            tenant.monitor.local_change(uuids);
+        }
+        Ok(undone)
+    }
+
+    fn subscribe(&self, user_id: Uuid) -> broadcast::Receiver<Vec<Uuid>> {
+        self.replicas.get(user_id).monitor.subscribe()
+    }
+
+    fn owners(&self) -> Vec<Uuid> {
+        self.replicas.owners()
+    }
 }
 
-pub fn create_task_repo<S: Storage + Sync>(replica: ArcRep<S>) -> Arc<TaskRepo<S>> {
-    Arc::new(TaskRepo::new(replica))
+/// the tasks `ops` change, each once
+fn touched(ops: &[Operation]) -> Vec<Uuid> {
+    ops.iter()
+        .filter_map(|op| match op {
+            Operation::Create { uuid }
+            | Operation::Delete { uuid, .. }
+            | Operation::Update { uuid, .. } => Some(*uuid),
+            Operation::UndoPoint => None,
+        })
+        .unique()
+        .collect()
+}
+
+pub fn create_task_repo(replicas: Arc<Replicas>) -> Arc<TaskRepo> {
+    Arc::new(TaskRepo::new(replicas))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::infra::sync_server::SyncBackend;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tokio_util::sync::CancellationToken;
+
+    async fn setup_repo() -> TaskRepo {
+        // one connection, every connection to :memory: is a database of its own
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations/taskdb").run(&pool).await.unwrap();
+        let data_dir = std::env::temp_dir().join(format!("taskbane-repo-{}", Uuid::new_v4()));
+        let replicas =
+            Replicas::with_backend(&pool, SyncBackend::None, data_dir, CancellationToken::new());
+        TaskRepo::new(replicas)
+    }
+
+    #[tokio::test]
+    async fn recurrences_are_created_once() {
+        let repo = setup_repo().await;
+        let user = Uuid::nil();
+        let now = Utc::now();
+        let template = Uuid::new_v4();
+        {
+            let tenant = repo.replicas.get(user);
+            let mut rep = tenant.replica.write().await;
+            let mut ops = Operations::new();
+            let mut task = rep.create_task(template, &mut ops).await.unwrap();
+            task.set_description("water the plants".into(), &mut ops)
+                .unwrap();
+            task.set_status(Status::Recurring, &mut ops).unwrap();
+            task.set_value("recur", Some("daily".into()), &mut ops)
+                .unwrap();
+            task.set_due(Some(now - chrono::Duration::hours(50)), &mut ops)
+                .unwrap();
+            rep.commit_operations(ops).await.unwrap();
+        }
+
+        // the background loop and a create racing each other
+        let (first, second) = tokio::join!(
+            repo.create_recurrences(user, template, now),
+            repo.create_recurrences(user, template, now),
+        );
+        let (first, second) = (first.unwrap(), second.unwrap());
+        // three due by now and the next one
+        assert_eq!(first + second, 4);
+        assert_eq!(first.min(second), 0);
+        assert_eq!(repo.create_recurrences(user, template, now).await.unwrap(), 0);
+
+        let children = repo
+            .list_matching(user, &|task: &Task| {
+                task.get_value("parent") == Some(template.to_string().as_str())
+            })
+            .await
+            .unwrap();
+        assert_eq!(children.len(), 4);
+        let imasks: Vec<_> = children
+            .iter()
+            .map(|(_, task, _)| task.get_value("imask").unwrap().to_owned())
+            .sorted()
+            .collect();
+        assert_eq!(imasks, ["0", "1", "2", "3"]);
+        let tenant = repo.replicas.get(user);
+        let parent = tenant.replica.write().await.get_task(template).await.unwrap().unwrap();
+        assert_eq!(parent.get_value("mask"), Some("----"));
+    }
+
+    #[tokio::test]
+    async fn done_stops_a_started_task() {
+        let repo = setup_repo().await;
+        let user = Uuid::nil();
+        let uuid = Uuid::new_v4();
+        {
+            let tenant = repo.replicas.get(user);
+            let mut rep = tenant.replica.write().await;
+            let mut ops = Operations::new();
+            let mut task = rep.create_task(uuid, &mut ops).await.unwrap();
+            task.set_description("write the report".into(), &mut ops)
+                .unwrap();
+            task.set_status(Status::Pending, &mut ops).unwrap();
+            rep.commit_operations(ops).await.unwrap();
+        }
+
+        repo.start_task(user, uuid).await.unwrap();
+        assert!(repo.get_task(user, uuid).await.unwrap().unwrap().is_active());
+        repo.mark_task_done(user, uuid).await.unwrap();
+
+        let task = repo.get_task(user, uuid).await.unwrap().unwrap();
+        assert_eq!(task.get_status(), Status::Completed);
+        assert!(!task.is_active());
+    }
 }
@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::core::{models::uda::Uda, ports::uda::UdaRepository};
+
+pub struct UdaSqlRepo {
+    pool: SqlitePool,
+}
+
+struct UdaRow {
+    name: String,
+    kind: String,
+    label: String,
+    allowed: String,
+    default_value: String,
+    coefficient: f64,
+}
+
+impl TryFrom<UdaRow> for Uda {
+    type Error = Error;
+
+    fn try_from(row: UdaRow) -> Result<Self> {
+        Ok(Uda::new(
+            row.name,
+            row.kind.parse()?,
+            row.label,
+            row.allowed
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            row.default_value,
+            row.coefficient,
+        ))
+    }
+}
+
+#[async_trait]
+impl UdaRepository for UdaSqlRepo {
+    async fn list(&self, user_id: Uuid) -> Result<Vec<Uda>> {
+        sqlx::query_as!(
+            UdaRow,
+            r#"
+                SELECT name, type AS kind, label, allowed, default_value, coefficient
+                FROM user_udas
+                WHERE user_id = ?
+                ORDER BY name
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(Uda::try_from)
+        .collect()
+    }
+
+    async fn save(&self, user_id: Uuid, uda: &Uda) -> Result<()> {
+        let kind = uda.kind.to_string();
+        let allowed = uda.values.join(",");
+        sqlx::query!(
+            r#"
+                INSERT INTO user_udas (user_id, name, type, label, allowed, default_value, coefficient)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT (user_id, name) DO UPDATE SET
+                    type = excluded.type,
+                    label = excluded.label,
+                    allowed = excluded.allowed,
+                    default_value = excluded.default_value,
+                    coefficient = excluded.coefficient
+            "#,
+            user_id,
+            uda.name,
+            kind,
+            uda.label,
+            allowed,
+            uda.default,
+            uda.coefficient,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+
+    async fn delete(&self, user_id: Uuid, name: &str) -> Result<()> {
+        sqlx::query!(
+            "DELETE FROM user_udas WHERE user_id = ? AND name = ?",
+            user_id,
+            name
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(Error::from)
+        .map(|_| ())
+    }
+}
+
+pub fn create_uda_repo(pool: &SqlitePool) -> Arc<UdaSqlRepo> {
+    Arc::new(UdaSqlRepo { pool: pool.clone() })
+}
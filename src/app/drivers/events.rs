@@ -0,0 +1,50 @@
+use std::{convert::Infallible, time::Duration};
+
+use axum::{
+    extract::State,
+    middleware,
+    response::{
+        sse::{Event, KeepAlive},
+        Sse,
+    },
+    routing, Router,
+};
+use futures::stream::StreamExt;
+use itertools::Itertools;
+use tokio_stream::{wrappers::BroadcastStream, Stream};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    core::services::TaskService,
+    infra::auth::{redirect_unauthorized_users, SessionAuthState},
+};
+
+type EventsState = (TaskService, CancellationToken);
+
+/// Server-sent events for open pages, `shutdown` ends the streams so they
+/// don't hold up a graceful shutdown
+pub fn events_routes(task_service: TaskService, shutdown: CancellationToken) -> axum::Router {
+    Router::new()
+        .route("/events", routing::get(get_events))
+        .layer(middleware::from_fn(redirect_unauthorized_users))
+        .with_state((task_service, shutdown))
+}
+
+/// a `task-changed` event with the space separated uuids of changed tasks,
+/// or `*` when the listener fell behind and everything may have changed
+async fn get_events(
+    auth_state: SessionAuthState,
+    State((task_service, shutdown)): State<EventsState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = BroadcastStream::new(task_service.changes(auth_state.user_id()))
+        .take_until(shutdown.cancelled_owned())
+        .map(|uuids| {
+            let data = match uuids {
+                Ok(uuids) => uuids.iter().join(" "),
+                Err(_) => "*".to_owned(),
+            };
+            Ok(Event::default().event("task-changed").data(data))
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(Duration::from_secs(30)))
+}
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::header,
+    response::IntoResponse,
+    routing, Router,
+};
+use serde::Deserialize;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{core::services::TaskService, infra::error::ApiError};
+
+/// calendar apps can't do webauthn, the secret token in the url stands in for a session
+pub fn feed_routes(task_service: TaskService) -> axum::Router {
+    Router::new()
+        .route("/feed/{token}", routing::get(get_feed))
+        .with_state(task_service)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedQuery {
+    #[serde(default)]
+    filter: String,
+}
+
+/// `/feed/<token>.ics`, the extension is optional but some clients want one
+async fn get_feed(
+    Path(token): Path<String>,
+    task_service: State<TaskService>,
+    query: Query<FeedQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let token = token
+        .strip_suffix(".ics")
+        .unwrap_or(&token)
+        .parse::<Uuid>()
+        .map_err(|_| ApiError::NotFound)?;
+
+    let feed = task_service
+        .ical_feed(token, &query.filter)
+        .await
+        .map_err(|err| {
+            info!("Error building feed: {err:?}");
+            ApiError::BadRequest {
+                message: err.to_string(),
+            }
+        })?
+        .ok_or(ApiError::NotFound)?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        feed,
+    ))
+}
@@ -1,23 +1,32 @@
 pub mod auth;
+pub mod events;
+pub mod feed;
 pub mod home;
+pub mod recurrence;
+pub mod report;
+pub mod settings;
+pub mod sync;
 pub mod task;
 
-use crate::core::services::{AuthService, TaskService, UserService};
+use crate::core::services::{
+    AuthService, ReportService, SettingsService, SyncService, TaskService, UserService,
+};
 #[cfg(debug_assertions)]
 use crate::infra::livereload;
 use axum::routing::get;
-#[cfg(debug_assertions)]
 use tokio_util::sync::CancellationToken;
 
 pub struct CreateDriverParams {
     pub app: axum::Router,
     #[cfg(debug_assertions)]
     pub rx: tokio::sync::oneshot::Receiver<()>,
-    #[cfg(debug_assertions)]
     pub shutdown_token: CancellationToken,
     pub user_service: UserService,
     pub auth_service: AuthService,
     pub task_service: TaskService,
+    pub report_service: ReportService,
+    pub settings_service: SettingsService,
+    pub sync_service: SyncService,
 }
 
 pub fn create_drivers(params: CreateDriverParams) -> axum::Router {
@@ -32,13 +41,24 @@ pub fn create_drivers(params: CreateDriverParams) -> axum::Router {
         .merge({
             #[cfg(debug_assertions)]
             {
-                livereload::live_reload(params.rx, params.shutdown_token)
+                livereload::live_reload(params.rx, params.shutdown_token.clone())
             }
             #[cfg(not(debug_assertions))]
             {
                 axum::Router::new()
             }
         })
+        .merge(report::report_routes(
+            params.report_service,
+            params.task_service.clone(),
+        ))
+        .merge(settings::settings_routes(params.settings_service))
+        .merge(feed::feed_routes(params.task_service.clone()))
+        .merge(sync::sync_routes(params.sync_service))
+        .merge(events::events_routes(
+            params.task_service.clone(),
+            params.shutdown_token,
+        ))
         .merge(task::task_routes(params.task_service))
 }
 
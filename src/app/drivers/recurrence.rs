@@ -0,0 +1,29 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+use crate::core::services::TaskService;
+
+/// templates are also expanded right away when one is created here,
+/// the loop picks up ones synced from other replicas and instances coming due
+const RECURRENCE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// runs until `shutdown` is cancelled, a pass under way is finished first
+pub fn start_recurrence_loop(task_service: TaskService, shutdown: CancellationToken) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RECURRENCE_INTERVAL);
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            match task_service.generate_recurring().await {
+                Ok(0) => {}
+                Ok(created) => info!("created {created} recurring task instances"),
+                Err(err) => info!("recurrence err: {err:?}"),
+            }
+        }
+        info!("recurrence loop stopped");
+    });
+}
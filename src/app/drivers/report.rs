@@ -0,0 +1,289 @@
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderName, HeaderValue},
+    middleware,
+    response::{Html, IntoResponse},
+    routing, Router,
+};
+use axum_extra::extract::Form;
+use derive_more::Constructor;
+use serde::Deserialize;
+use tower_sessions::Session;
+use tracing::info;
+
+use super::task::TaskListPage;
+use crate::{
+    core::{
+        models::report::{Report, COLUMNS},
+        services::{ReportInput, ReportService, TaskService},
+    },
+    infra::{
+        alerts::{Alert, AlertLevel},
+        askama::{Globals, HtmlTemplate},
+        auth::{redirect_unauthorized_users, SessionAuthState},
+        error::{ApiError, AppError},
+    },
+};
+
+#[derive(Clone)]
+struct ReportServices {
+    report_service: ReportService,
+    task_service: TaskService,
+}
+
+pub fn report_routes(report_service: ReportService, task_service: TaskService) -> axum::Router {
+    Router::new()
+        .route("/report", routing::get(get_reports))
+        .route("/report", routing::post(post_report))
+        .route("/report/nav", routing::get(get_report_nav))
+        .route("/report/{name}", routing::get(get_report))
+        .route("/report/{name}/delete", routing::post(post_delete_report))
+        .layer(middleware::from_fn(redirect_unauthorized_users))
+        .with_state(ReportServices {
+            report_service,
+            task_service,
+        })
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "partials/report-nav.html")]
+struct ReportNav {
+    reports: Vec<Report>,
+}
+
+async fn get_report_nav(
+    auth_state: SessionAuthState,
+    State(services): State<ReportServices>,
+) -> Result<impl IntoResponse, ApiError> {
+    let reports = services
+        .report_service
+        .list(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error listing reports: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok(HtmlTemplate(ReportNav::new(reports)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportQuery {
+    #[serde(default)]
+    filter: String,
+}
+
+async fn get_report(
+    Path(name): Path<String>,
+    session: Session,
+    auth_state: SessionAuthState,
+    query: Query<ReportQuery>,
+    State(services): State<ReportServices>,
+) -> Result<impl IntoResponse, AppError> {
+    let report = services
+        .report_service
+        .get(auth_state.user_id(), &name)
+        .await
+        .map_err(|err| {
+            info!("Error getting report: {err:?}");
+            AppError::NotFound
+        })?;
+
+    let mut globals = Globals::fetch(&session).await;
+
+    // extra terms narrow the report, a bad one falls back to the report alone
+    let combined = report.filter_with(&query.filter);
+    let filter = match services
+        .task_service
+        .parse_filter(auth_state.user_id(), &combined)
+        .await
+    {
+        Ok(filter) => filter,
+        Err(err) => {
+            globals = globals.push_alert(Alert::new(
+                AlertLevel::Error,
+                format!("Invalid filter: {err}"),
+            ));
+            services
+                .task_service
+                .parse_filter(auth_state.user_id(), &report.filter)
+                .await
+                .map_err(|err| {
+                    info!("Error parsing report filter: {err:?}");
+                    AppError::InternalServerError
+                })?
+        }
+    };
+
+    let mut tasks = services
+        .task_service
+        .list(auth_state.user_id(), Some(&filter))
+        .await
+        .map_err(|err| {
+            info!("Error getting tasks: {err:?}");
+            AppError::InternalServerError
+        })?;
+    report.sort.apply(&mut tasks);
+
+    let templ = TaskListPage::new(
+        auth_state.is_authed(),
+        report.name.clone(),
+        format!("/report/{}", report.name),
+        tasks,
+        query.0.filter,
+        report.columns,
+        globals,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "reports.html")]
+struct ReportsPage {
+    is_authed: bool,
+    reports: Vec<Report>,
+    /// report loaded into the form
+    editing: Option<Report>,
+    globals: Globals,
+}
+
+impl ReportsPage {
+    fn all_columns(&self) -> String {
+        COLUMNS.join(",")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportsQuery {
+    edit: Option<String>,
+}
+
+async fn get_reports(
+    session: Session,
+    auth_state: SessionAuthState,
+    query: Query<ReportsQuery>,
+    State(services): State<ReportServices>,
+) -> Result<impl IntoResponse, AppError> {
+    let reports = services
+        .report_service
+        .list(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error listing reports: {err:?}");
+            AppError::InternalServerError
+        })?;
+    let editing = query
+        .edit
+        .as_ref()
+        .and_then(|name| reports.iter().find(|report| &report.name == name))
+        .cloned();
+
+    let templ = ReportsPage::new(
+        auth_state.is_authed(),
+        reports,
+        editing,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+#[derive(Deserialize)]
+pub struct ReportForm {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    filter: String,
+    #[serde(default)]
+    sort: String,
+    columns: String,
+}
+
+impl From<ReportForm> for ReportInput {
+    fn from(value: ReportForm) -> Self {
+        Self::new(
+            value.name,
+            value.description,
+            value.filter,
+            value.sort,
+            value.columns,
+        )
+    }
+}
+
+async fn render_reports(
+    session: &Session,
+    auth_state: &SessionAuthState,
+    services: &ReportServices,
+    alert: Alert,
+) -> Result<impl IntoResponse, ApiError> {
+    let reports = services
+        .report_service
+        .list(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error listing reports: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let globals = Globals::fetch(session).await.push_alert(alert);
+
+    let reports_page = ReportsPage::new(true, reports, None, globals)
+        .render()
+        .map_err(|err| {
+            info!("Error rendering reports: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/report"),
+        )],
+        Html(reports_page),
+    ))
+}
+
+async fn post_report(
+    session: Session,
+    auth_state: SessionAuthState,
+    State(services): State<ReportServices>,
+    form: Form<ReportForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    let report = services
+        .report_service
+        .save(auth_state.user_id(), form.0.into())
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let alert = Alert::new(
+        AlertLevel::Success,
+        format!("Report {} saved!", report.name),
+    );
+
+    render_reports(&session, &auth_state, &services, alert).await
+}
+
+async fn post_delete_report(
+    Path(name): Path<String>,
+    session: Session,
+    auth_state: SessionAuthState,
+    State(services): State<ReportServices>,
+) -> Result<impl IntoResponse, ApiError> {
+    services
+        .report_service
+        .delete(auth_state.user_id(), &name)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let alert = Alert::new(AlertLevel::Success, format!("Report {name} removed!"));
+
+    render_reports(&session, &auth_state, &services, alert).await
+}
@@ -0,0 +1,391 @@
+use askama::Template;
+use axum::{
+    extract::{Multipart, Path, State},
+    http::{HeaderName, HeaderValue},
+    middleware,
+    response::{Html, IntoResponse},
+    routing, Router,
+};
+use axum_extra::extract::Form;
+use chrono::Weekday;
+use serde::Deserialize;
+use tower_sessions::Session;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{
+    core::{models::uda::Uda, services::SettingsService},
+    infra::{
+        alerts::{Alert, AlertLevel},
+        askama::{Globals, HtmlTemplate},
+        auth::{redirect_unauthorized_users, SessionAuthState},
+        error::{ApiError, AppError},
+    },
+};
+
+pub fn settings_routes(settings_service: SettingsService) -> axum::Router {
+    Router::new()
+        .route("/settings", routing::get(get_settings))
+        .route("/settings/urgency", routing::post(post_urgency))
+        .route("/settings/reset", routing::post(post_reset))
+        .route("/settings/calendar", routing::post(post_calendar))
+        .route("/settings/feed", routing::post(post_feed))
+        .route("/settings/feed/revoke", routing::post(post_revoke_feed))
+        .route("/settings/import", routing::post(post_import))
+        .route("/settings/uda", routing::post(post_uda))
+        .route(
+            "/settings/uda/{name}/delete",
+            routing::post(post_delete_uda),
+        )
+        .layer(middleware::from_fn(redirect_unauthorized_users))
+        .with_state(settings_service)
+}
+
+#[derive(Debug, Clone, Template)]
+#[template(path = "settings.html")]
+struct SettingsPage {
+    is_authed: bool,
+    /// every coefficient in effect, (key, value, overridden)
+    coefficients: Vec<(String, f64, bool)>,
+    /// saved settings that aren't urgency coefficients, mostly from imports
+    other: Vec<(String, String)>,
+    udas: Vec<Uda>,
+    /// (timezone, weekstart) the calendar uses
+    calendar: (String, String),
+    /// secret of the calendar feed url, `None` until one is made
+    feed_token: Option<Uuid>,
+    globals: Globals,
+}
+
+impl SettingsPage {
+    fn timezones(&self) -> impl Iterator<Item = &'static str> {
+        chrono_tz::TZ_VARIANTS.iter().map(|tz| tz.name())
+    }
+}
+
+async fn settings_page(
+    settings_service: &SettingsService,
+    auth_state: &SessionAuthState,
+    globals: Globals,
+) -> anyhow::Result<SettingsPage> {
+    let user_id = auth_state.user_id();
+    let overrides = settings_service.overrides(user_id).await?;
+    let coefficients = settings_service
+        .urgency(user_id)
+        .await?
+        .entries()
+        .into_iter()
+        .map(|(key, value)| {
+            let overridden = overrides.iter().any(|(saved, _)| *saved == key);
+            (key, value, overridden)
+        })
+        .collect();
+    let other = overrides
+        .into_iter()
+        .filter(|(key, _)| !key.starts_with("urgency."))
+        .filter(|(key, _)| !["timezone", "weekstart"].contains(&key.as_str()))
+        .collect();
+    let udas = settings_service.udas(user_id).await?;
+    let calendar = (
+        settings_service.timezone(user_id).await?.name().to_owned(),
+        match settings_service.weekstart(user_id).await? {
+            Weekday::Sun => "sunday".to_owned(),
+            _ => "monday".to_owned(),
+        },
+    );
+
+    Ok(SettingsPage {
+        is_authed: auth_state.is_authed(),
+        coefficients,
+        other,
+        udas,
+        calendar,
+        feed_token: settings_service.feed_token(user_id).await?,
+        globals,
+    })
+}
+
+async fn get_settings(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+) -> Result<impl IntoResponse, AppError> {
+    let page = settings_page(&settings_service, &auth_state, Globals::fetch(&session).await)
+        .await
+        .map_err(|err| {
+            info!("Error getting settings: {err:?}");
+            AppError::InternalServerError
+        })?;
+
+    Ok(HtmlTemplate(page))
+}
+
+async fn render_settings(
+    session: &Session,
+    auth_state: &SessionAuthState,
+    settings_service: &SettingsService,
+    alerts: Vec<Alert>,
+) -> Result<impl IntoResponse, ApiError> {
+    let globals = alerts
+        .into_iter()
+        .fold(Globals::fetch(session).await, Globals::push_alert);
+
+    let settings_page = settings_page(settings_service, auth_state, globals)
+        .await
+        .and_then(|page| page.render().map_err(anyhow::Error::from))
+        .map_err(|err| {
+            info!("Error rendering settings: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/settings"),
+        )],
+        Html(settings_page),
+    ))
+}
+
+#[derive(Deserialize)]
+pub struct UrgencyForm {
+    key: String,
+    value: String,
+}
+
+async fn post_urgency(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+    form: Form<UrgencyForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    settings_service
+        .set_urgency(auth_state.user_id(), &form.key, &form.value)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let alert = Alert::new(AlertLevel::Success, format!("{} saved!", form.key));
+
+    render_settings(&session, &auth_state, &settings_service, vec![alert]).await
+}
+
+#[derive(Deserialize)]
+pub struct ResetForm {
+    key: String,
+}
+
+async fn post_reset(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+    form: Form<ResetForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    settings_service
+        .reset(auth_state.user_id(), &form.key)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let alert = Alert::new(AlertLevel::Success, format!("{} reset!", form.key));
+
+    render_settings(&session, &auth_state, &settings_service, vec![alert]).await
+}
+
+#[derive(Deserialize)]
+pub struct CalendarForm {
+    timezone: String,
+    weekstart: String,
+}
+
+async fn post_calendar(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+    form: Form<CalendarForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    settings_service
+        .set_calendar(auth_state.user_id(), &form.timezone, &form.weekstart)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let alert = Alert::new(AlertLevel::Success, "Calendar settings saved!".to_owned());
+
+    render_settings(&session, &auth_state, &settings_service, vec![alert]).await
+}
+
+async fn post_feed(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+) -> Result<impl IntoResponse, ApiError> {
+    settings_service
+        .reset_feed_token(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error creating feed token: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let alert = Alert::new(
+        AlertLevel::Success,
+        "New feed url created, the old one no longer works".to_owned(),
+    );
+
+    render_settings(&session, &auth_state, &settings_service, vec![alert]).await
+}
+
+async fn post_revoke_feed(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+) -> Result<impl IntoResponse, ApiError> {
+    settings_service
+        .revoke_feed(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error revoking feed token: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let alert = Alert::new(AlertLevel::Success, "Feed url revoked!".to_owned());
+
+    render_settings(&session, &auth_state, &settings_service, vec![alert]).await
+}
+
+async fn post_import(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, ApiError> {
+    let bad_request = |err: axum::extract::multipart::MultipartError| ApiError::BadRequest {
+        message: err.body_text(),
+    };
+
+    // the uploaded file and the pasted text are imported together
+    let mut taskrc = String::new();
+    while let Some(field) = multipart.next_field().await.map_err(bad_request)? {
+        if !matches!(field.name(), Some("file" | "text")) {
+            continue;
+        }
+        taskrc.push_str(&field.text().await.map_err(bad_request)?);
+        taskrc.push('\n');
+    }
+    if taskrc.trim().is_empty() {
+        return Err(ApiError::BadRequest {
+            message: "Upload or paste a .taskrc to import".to_owned(),
+        });
+    }
+
+    let import = settings_service
+        .import_taskrc(auth_state.user_id(), &taskrc)
+        .await
+        .map_err(|err| {
+            info!("Error importing taskrc: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let mut alerts = vec![Alert::new(
+        AlertLevel::Success,
+        format!(
+            "Imported {} urgency coefficients, {} reports, {} attributes and {} settings!",
+            import.urgency, import.reports, import.udas, import.settings
+        ),
+    )];
+    if !import.skipped.is_empty() {
+        alerts.push(Alert::new(
+            AlertLevel::Warning,
+            format!("Skipped {}", import.skipped.join(", ")),
+        ));
+    }
+
+    render_settings(&session, &auth_state, &settings_service, alerts).await
+}
+
+#[derive(Deserialize)]
+pub struct UdaForm {
+    name: String,
+    kind: String,
+    #[serde(default)]
+    label: String,
+    /// comma separated
+    #[serde(default)]
+    values: String,
+    #[serde(default)]
+    default: String,
+    #[serde(default)]
+    coefficient: String,
+}
+
+impl TryFrom<UdaForm> for Uda {
+    type Error = anyhow::Error;
+
+    fn try_from(form: UdaForm) -> anyhow::Result<Self> {
+        Ok(Uda::new(
+            form.name.trim().to_owned(),
+            form.kind.parse()?,
+            form.label.trim().to_owned(),
+            form.values
+                .split(',')
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_owned)
+                .collect(),
+            form.default.trim().to_owned(),
+            match form.coefficient.trim() {
+                "" => 0.0,
+                coefficient => coefficient
+                    .parse()
+                    .map_err(|_| anyhow::anyhow!("'{coefficient}' is not a number"))?,
+            },
+        ))
+    }
+}
+
+async fn post_uda(
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+    form: Form<UdaForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    let bad_request = |err: anyhow::Error| ApiError::BadRequest {
+        message: err.to_string(),
+    };
+    let uda = settings_service
+        .save_uda(
+            auth_state.user_id(),
+            form.0.try_into().map_err(bad_request)?,
+        )
+        .await
+        .map_err(bad_request)?;
+
+    let alert = Alert::new(
+        AlertLevel::Success,
+        format!("Attribute {} saved!", uda.name),
+    );
+
+    render_settings(&session, &auth_state, &settings_service, vec![alert]).await
+}
+
+async fn post_delete_uda(
+    Path(name): Path<String>,
+    session: Session,
+    auth_state: SessionAuthState,
+    settings_service: State<SettingsService>,
+) -> Result<impl IntoResponse, ApiError> {
+    settings_service
+        .delete_uda(auth_state.user_id(), &name)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let alert = Alert::new(AlertLevel::Success, format!("Attribute {name} removed!"));
+
+    render_settings(&session, &auth_state, &settings_service, vec![alert]).await
+}
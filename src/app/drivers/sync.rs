@@ -0,0 +1,218 @@
+use askama::Template;
+use axum::{
+    extract::State,
+    http::{HeaderName, HeaderValue},
+    middleware,
+    response::{Html, IntoResponse},
+    routing, Router,
+};
+use axum_extra::extract::Form;
+use derive_more::Constructor;
+use serde::Deserialize;
+use tower_sessions::Session;
+use tracing::info;
+
+use crate::{
+    core::{
+        models::{
+            account::TaskAccount,
+            sync::{SyncState, SyncStatus},
+        },
+        services::SyncService,
+    },
+    infra::{
+        alerts::{Alert, AlertLevel},
+        askama::{Globals, HtmlTemplate},
+        auth::{redirect_unauthorized_users, SessionAuthState},
+        error::{ApiError, AppError},
+    },
+};
+
+pub fn sync_routes(sync_service: SyncService) -> axum::Router {
+    Router::new()
+        .route("/sync", routing::get(get_sync))
+        .route("/sync", routing::post(post_sync))
+        .route("/sync/nav", routing::get(get_sync_nav))
+        .route("/sync/account", routing::post(post_account))
+        .route("/sync/account/remove", routing::post(post_remove_account))
+        .layer(middleware::from_fn(redirect_unauthorized_users))
+        .with_state(sync_service)
+}
+
+#[derive(Debug, Clone, Template)]
+#[template(path = "sync.html")]
+struct SyncPage {
+    is_authed: bool,
+    status: SyncStatus,
+    /// whether accounts can be stored, they need `TASK_CREDENTIALS_KEY`
+    accounts_enabled: bool,
+    account: Option<TaskAccount>,
+    globals: Globals,
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "partials/sync-nav.html")]
+struct SyncNav {
+    status: SyncStatus,
+}
+
+async fn sync_page(
+    sync_service: &SyncService,
+    auth_state: &SessionAuthState,
+    status: Option<SyncStatus>,
+    globals: Globals,
+) -> anyhow::Result<SyncPage> {
+    let user_id = auth_state.user_id();
+    let status = match status {
+        Some(status) => status,
+        None => sync_service.status(user_id).await?,
+    };
+    let accounts_enabled = sync_service.accounts_enabled();
+    let account = match accounts_enabled {
+        true => sync_service.account(user_id).await?,
+        false => None,
+    };
+
+    Ok(SyncPage {
+        is_authed: auth_state.is_authed(),
+        status,
+        accounts_enabled,
+        account,
+        globals,
+    })
+}
+
+async fn get_sync(
+    session: Session,
+    auth_state: SessionAuthState,
+    sync_service: State<SyncService>,
+) -> Result<impl IntoResponse, AppError> {
+    let page = sync_page(&sync_service, &auth_state, None, Globals::fetch(&session).await)
+        .await
+        .map_err(|err| {
+            info!("Error getting sync status: {err:?}");
+            AppError::InternalServerError
+        })?;
+
+    Ok(HtmlTemplate(page))
+}
+
+async fn render_sync(
+    session: &Session,
+    auth_state: &SessionAuthState,
+    sync_service: &SyncService,
+    status: Option<SyncStatus>,
+    alert: Alert,
+) -> Result<impl IntoResponse, ApiError> {
+    let globals = Globals::fetch(session).await.push_alert(alert);
+
+    let page = sync_page(sync_service, auth_state, status, globals)
+        .await
+        .and_then(|page| page.render().map_err(anyhow::Error::from))
+        .map_err(|err| {
+            info!("Error rendering sync page: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/sync"),
+        )],
+        Html(page),
+    ))
+}
+
+async fn get_sync_nav(
+    auth_state: SessionAuthState,
+    sync_service: State<SyncService>,
+) -> Result<impl IntoResponse, ApiError> {
+    let status = sync_service
+        .status(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error getting sync status: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok(HtmlTemplate(SyncNav::new(status)))
+}
+
+async fn post_sync(
+    session: Session,
+    auth_state: SessionAuthState,
+    sync_service: State<SyncService>,
+) -> Result<impl IntoResponse, ApiError> {
+    let status = sync_service
+        .sync_now(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error syncing: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let alert = match (status.state(), &status.last_error) {
+        (SyncState::Syncing, _) => Alert::new(
+            AlertLevel::Info,
+            "Still syncing, check back in a moment".to_owned(),
+        ),
+        (_, Some(err)) => Alert::new(AlertLevel::Error, format!("Sync failed: {err}")),
+        _ => Alert::new(AlertLevel::Success, "Synced!".to_owned()),
+    };
+
+    render_sync(&session, &auth_state, &sync_service, Some(status), alert).await
+}
+
+#[derive(Deserialize)]
+pub struct AccountForm {
+    url: String,
+    client_id: String,
+    encryption_secret: String,
+}
+
+async fn post_account(
+    session: Session,
+    auth_state: SessionAuthState,
+    sync_service: State<SyncService>,
+    form: Form<AccountForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    let account = sync_service
+        .set_account(
+            auth_state.user_id(),
+            &form.url,
+            &form.client_id,
+            &form.encryption_secret,
+        )
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let alert = Alert::new(
+        AlertLevel::Success,
+        format!("Your tasks now sync with {}", account.url),
+    );
+
+    render_sync(&session, &auth_state, &sync_service, None, alert).await
+}
+
+async fn post_remove_account(
+    session: Session,
+    auth_state: SessionAuthState,
+    sync_service: State<SyncService>,
+) -> Result<impl IntoResponse, ApiError> {
+    sync_service
+        .remove_account(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error removing task account: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let alert = Alert::new(
+        AlertLevel::Success,
+        "Account removed, you are back on the shared tasks".to_owned(),
+    );
+
+    render_sync(&session, &auth_state, &sync_service, None, alert).await
+}
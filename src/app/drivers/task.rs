@@ -7,15 +7,30 @@ use axum::{
     routing, Router,
 };
 use axum_extra::extract::Form;
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
 use derive_more::Constructor;
 use serde::Deserialize;
-use taskchampion::Annotation;
+use taskchampion::{Annotation, Status};
 use tower_sessions::Session;
 use tracing::info;
 use uuid::Uuid;
 
 use crate::{
-    core::{models::task::TaskDto, services::TaskService},
+    core::{
+        models::{
+            board::{BoardColumn, Grouping},
+            calendar::{Calendar, CalendarView},
+            dependency::TaskDependencies,
+            history::HistoryEntry,
+            project::ProjectSummary,
+            report::Columns,
+            task::TaskDto,
+            uda::{Uda, UdaType},
+            undo::UndoPreview,
+        },
+        services::TaskService,
+    },
     infra::{
         alerts::{Alert, AlertLevel},
         askama::{Globals, HtmlTemplate},
@@ -33,38 +48,100 @@ pub fn task_routes(task_service: TaskService) -> axum::Router {
             "/tasks",
             routing::get(async || Redirect::permanent("/task")),
         )
+        .route("/task/trash", routing::get(get_trash))
+        .route("/task/completed", routing::get(get_completed))
+        .route("/task/waiting", routing::get(get_waiting))
+        .route("/task/bulk", routing::post(post_bulk))
+        .route("/task/undo", routing::get(get_undo))
+        .route("/task/undo", routing::post(post_undo))
+        .route("/task/search", routing::get(get_search))
+        .route("/task/search/results", routing::get(get_search_results))
         .route("/task/{id}", routing::get(get_task))
+        .route("/task/{id}/graph", routing::get(get_task_graph))
+        .route("/task/{id}/history", routing::get(get_task_history))
+        .route("/task/{id}/card", routing::get(get_task_card))
+        .route("/task/{id}/edit", routing::get(get_edit_task))
+        .route("/task/{id}/edit", routing::post(post_edit_task))
         .route("/task/{id}/confirm-done", routing::get(get_confirm_done))
         .route("/task/{id}/done", routing::post(post_mark_task_down))
+        .route("/task/{id}/confirm-delete", routing::get(get_confirm_delete))
+        .route("/task/{id}/delete", routing::post(post_delete_task))
+        .route("/task/{id}/undelete", routing::post(post_undelete_task))
+        .route("/task/{id}/reopen", routing::post(post_reopen_task))
+        .route("/task/{id}/start", routing::post(post_start_task))
+        .route("/task/{id}/stop", routing::post(post_stop_task))
         .route("/task/date/parse", routing::get(get_datetime))
         .route("/task/annotate", routing::patch(patch_annotate))
+        .route("/project", routing::get(get_projects))
+        .route("/calendar", routing::get(get_calendar))
+        .route("/board", routing::get(get_board))
+        .route("/board/move", routing::post(post_board_move))
         .layer(middleware::from_fn(redirect_unauthorized_users))
         .with_state(task_service)
 }
 
 #[derive(Debug, Clone, Template, Constructor)]
 #[template(path = "task.html")]
-struct TaskListPage {
+pub(super) struct TaskListPage {
     is_authed: bool,
+    title: String,
+    /// where the filter form submits to
+    action: String,
     tasks: Vec<TaskDto>,
+    filter: String,
+    columns: Columns,
     globals: Globals,
 }
 
+impl TaskListPage {
+    fn pending(is_authed: bool, tasks: Vec<TaskDto>, filter: String, globals: Globals) -> Self {
+        Self::new(
+            is_authed,
+            "Tasks".to_owned(),
+            "/task".to_owned(),
+            tasks,
+            filter,
+            Columns::default(),
+            globals,
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskListQuery {
+    #[serde(default)]
+    filter: String,
+}
+
 pub async fn get_tasks(
     session: Session,
     auth_state: SessionAuthState,
+    query: Query<TaskListQuery>,
     task_service: State<TaskService>,
 ) -> Result<impl IntoResponse, AppError> {
-    let tasks = task_service.list().await.map_err(|err| {
+    let mut globals = Globals::fetch(&session).await;
+
+    // a bad filter shouldn't lose the list, show everything with the parse error
+    let filter = match task_service
+        .parse_filter(auth_state.user_id(), &query.filter)
+        .await
+    {
+        Ok(filter) => Some(filter).filter(|filter| !filter.is_empty()),
+        Err(err) => {
+            globals = globals.push_alert(Alert::new(
+                AlertLevel::Error,
+                format!("Invalid filter: {err}"),
+            ));
+            None
+        }
+    };
+
+    let tasks = task_service.list(auth_state.user_id(), filter.as_ref()).await.map_err(|err| {
         info!("Error getting tasks: {:?}", err);
         AppError::InternalServerError
     })?;
 
-    let templ = TaskListPage::new(
-        auth_state.is_authed(),
-        tasks,
-        Globals::fetch(&session).await,
-    );
+    let templ = TaskListPage::pending(auth_state.is_authed(), tasks, query.0.filter, globals);
 
     Ok(HtmlTemplate(templ))
 }
@@ -75,34 +152,130 @@ struct CreateTaskPage {
     is_authed: bool,
     globals: Globals,
     tasks_json: String,
+    projects: Vec<String>,
+    udas: Vec<UdaField>,
+    /// prefilled due, set when coming from a calendar day
+    due: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewTaskQuery {
+    #[serde(default)]
+    due: String,
 }
 
 pub async fn get_create_task(
     session: Session,
+    auth_state: SessionAuthState,
     task_service: State<TaskService>,
+    query: Query<NewTaskQuery>,
 ) -> impl IntoResponse {
+    let tasks_json = tasks_json(&task_service, auth_state.user_id(), None).await;
+
+    let projects = project_names(&task_service, auth_state.user_id()).await;
+    let udas = uda_schema(&task_service, auth_state.user_id())
+        .await
+        .into_iter()
+        .map(|uda| UdaField {
+            value: uda.default.clone(),
+            uda,
+        })
+        .collect();
+
+    let create_page = CreateTaskPage::new(
+        true,
+        Globals::fetch(&session).await,
+        tasks_json,
+        projects,
+        udas,
+        query.0.due,
+    );
+
+    HtmlTemplate(create_page)
+}
+
+/// existing projects to suggest on the project input
+async fn project_names(task_service: &TaskService, user_id: Uuid) -> Vec<String> {
+    task_service
+        .projects(user_id)
+        .await
+        .map(|projects| projects.into_iter().map(|project| project.name).collect())
+        .inspect_err(|err| info!("Error getting projects: {err:?}"))
+        .unwrap_or_default()
+}
+
+/// a typed input for a schema uda on the create and edit forms
+#[derive(Debug, Clone)]
+struct UdaField {
+    uda: Uda,
+    value: String,
+}
+
+impl UdaField {
+    fn input_type(&self) -> &str {
+        match self.uda.kind {
+            UdaType::Numeric => "number",
+            _ => "text",
+        }
+    }
+
+    fn placeholder(&self) -> &str {
+        match self.uda.kind {
+            UdaType::Date => "tomorrow, fri, 2026-03-01",
+            UdaType::Duration => "3h, 2d, 1w",
+            _ => "",
+        }
+    }
+}
+
+async fn uda_schema(task_service: &TaskService, user_id: Uuid) -> Vec<Uda> {
+    task_service
+        .udas(user_id)
+        .await
+        .inspect_err(|err| info!("Error getting udas: {err:?}"))
+        .unwrap_or_default()
+}
+
+/// a task's udas as (label, value), labels and formatting come from the schema
+async fn uda_rows(
+    task_service: &TaskService,
+    user_id: Uuid,
+    task: &TaskDto,
+) -> Vec<(String, String)> {
+    let schema = uda_schema(task_service, user_id).await;
+    task.udas
+        .iter()
+        .map(
+            |(name, value)| match schema.iter().find(|uda| &uda.name == name) {
+                Some(uda) => (uda.label().to_owned(), uda.display(value, task.tz)),
+                None => (name.clone(), value.clone()),
+            },
+        )
+        .collect()
+}
+
+/// serialize pending tasks for the deps typeahead,
+/// leaving out `exclude` so a task can't depend on itself
+async fn tasks_json(task_service: &TaskService, user_id: Uuid, exclude: Option<Uuid>) -> String {
     #[derive(serde::Serialize, Constructor)]
     struct TaskSearchDto {
         id: usize,
         uuid: Uuid,
         description: String,
     }
-    let tasks_json = task_service
-        .list()
+    task_service
+        .list(user_id, None)
         .await
         .map(|tasks| {
             tasks
                 .iter()
+                .filter(|task| Some(task.uuid) != exclude)
                 .map(|task| TaskSearchDto::new(task.id, task.uuid, task.description.clone()))
                 .collect::<Vec<_>>()
         })
         .and_then(|tasks| serde_json::to_string(&tasks).map_err(anyhow::Error::from))
         .inspect_err(|err| info!("err serialize tasks {err:?}"))
-        .unwrap_or_default();
-
-    let create_page = CreateTaskPage::new(true, Globals::fetch(&session).await, tasks_json);
-
-    HtmlTemplate(create_page)
+        .unwrap_or_default()
 }
 
 #[derive(Deserialize)]
@@ -110,34 +283,63 @@ pub struct CreateTaskQuery {
     pub description: String,
     pub priority: String,
     #[serde(default)]
+    pub project: String,
+    #[serde(default)]
     pub deps: Vec<Uuid>,
     #[serde(default)]
     pub tags: Vec<String>,
     pub due: Option<String>,
+    pub wait: Option<String>,
+    pub scheduled: Option<String>,
+    #[serde(default)]
+    pub recur: String,
+    pub until: Option<String>,
+    /// one entry per schema uda, paired up by position
+    #[serde(default)]
+    pub uda_names: Vec<String>,
+    #[serde(default)]
+    pub uda_values: Vec<String>,
+}
+
+/// the user's wall clock, form dates are read on it
+async fn user_now(task_service: &TaskService, user_id: Uuid) -> Result<DateTime<Tz>, ApiError> {
+    task_service.now(user_id).await.map_err(|err| {
+        info!("Error getting timezone: {err:?}");
+        ApiError::InternalServerError
+    })
 }
 
 pub async fn post_create_task(
     session: Session,
+    auth_state: SessionAuthState,
     task_service: State<TaskService>,
     query: Form<CreateTaskQuery>,
 ) -> Result<impl IntoResponse, ApiError> {
     let id = task_service
-        .create_task(query.0.into())
+        .create_task(
+            auth_state.user_id(),
+            (query.0, user_now(&task_service, auth_state.user_id()).await?).try_into()?,
+        )
         .await
         .map_err(|err| ApiError::BadRequest {
             message: err.to_string(),
         })?;
 
-    let tasks = task_service.list().await.map_err(|err| {
+    let tasks = task_service.list(auth_state.user_id(), None).await.map_err(|err| {
         info!("Error getting tasks: {:?}", err);
         ApiError::InternalServerError
     })?;
 
-    let alert = Alert::new(AlertLevel::Success, format!("Task created with id {id}!"));
+    // templates live outside the working set, their instances get the ids
+    let message = match id {
+        0 => "Recurring task created!".to_owned(),
+        id => format!("Task created with id {id}!"),
+    };
+    let alert = Alert::new(AlertLevel::Success, message).undoable();
 
     let globals = Globals::fetch(&session).await.push_alert(alert);
 
-    let tasks_page = TaskListPage::new(true, tasks, globals)
+    let tasks_page = TaskListPage::pending(true, tasks, String::new(), globals)
         .render()
         .map_err(|err| {
             info!("Error rendering alert: {err:?}");
@@ -158,6 +360,8 @@ pub async fn post_create_task(
 struct TaskPage {
     is_authed: bool,
     task: TaskDto,
+    /// (label, value) of the task's udas
+    udas: Vec<(String, String)>,
     globals: Globals,
 }
 
@@ -167,27 +371,358 @@ pub async fn get_task(
     auth_state: SessionAuthState,
     task_service: State<TaskService>,
 ) -> Result<impl IntoResponse, AppError> {
-    let task = task_service.get_task(id).await.map_err(|err| {
+    let task = task_service.get_task(auth_state.user_id(), id).await.map_err(|err| {
         info!("Error getting tasks: {:?}", err);
         AppError::NotFound
     })?;
 
-    let templ = TaskPage::new(auth_state.is_authed(), task, Globals::fetch(&session).await);
+    let udas = uda_rows(&task_service, auth_state.user_id(), &task).await;
+    let templ = TaskPage::new(
+        auth_state.is_authed(),
+        task,
+        udas,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "task_graph.html")]
+struct TaskGraphPage {
+    is_authed: bool,
+    graph: TaskDependencies,
+    globals: Globals,
+}
+
+pub async fn get_task_graph(
+    Path(id): Path<Uuid>,
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let graph = task_service
+        .dependencies(auth_state.user_id(), id)
+        .await
+        .map_err(|err| {
+            info!("Error getting dependencies: {err:?}");
+            AppError::NotFound
+        })?;
+
+    let templ = TaskGraphPage::new(auth_state.is_authed(), graph, Globals::fetch(&session).await);
+
+    Ok(HtmlTemplate(templ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "partials/task-history.html")]
+struct TaskHistory {
+    entries: Vec<HistoryEntry>,
+}
+
+pub async fn get_task_history(
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    let entries = task_service.history(auth_state.user_id(), id).await.map_err(|err| {
+        info!("Error getting task history: {err:?}");
+        ApiError::InternalServerError
+    })?;
+
+    Ok(HtmlTemplate(TaskHistory::new(entries)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CalendarQuery {
+    #[serde(default)]
+    view: String,
+    date: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "calendar.html")]
+struct CalendarPage {
+    is_authed: bool,
+    calendar: Calendar,
+    globals: Globals,
+}
+
+pub async fn get_calendar(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+    query: Query<CalendarQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let view = match query.view.as_str() {
+        "" => CalendarView::default(),
+        view => view.parse().map_err(|err: anyhow::Error| ApiError::BadRequest {
+            message: err.to_string(),
+        })?,
+    };
+    let calendar = task_service
+        .calendar(auth_state.user_id(), view, query.date)
+        .await
+        .map_err(|err| {
+            info!("Error getting calendar: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let templ = CalendarPage::new(
+        auth_state.is_authed(),
+        calendar,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BoardQuery {
+    #[serde(default)]
+    group: String,
+}
+
+impl BoardQuery {
+    fn grouping(&self) -> Result<Grouping, ApiError> {
+        if self.group.is_empty() {
+            return Ok(Grouping::default());
+        }
+        self.group.parse().map_err(|err: anyhow::Error| ApiError::BadRequest {
+            message: err.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "board.html")]
+struct BoardPage {
+    is_authed: bool,
+    grouping: Grouping,
+    columns: Vec<BoardColumn>,
+    globals: Globals,
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "partials/board.html")]
+struct Board {
+    grouping: Grouping,
+    columns: Vec<BoardColumn>,
+}
+
+pub async fn get_board(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+    query: Query<BoardQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let grouping = query.grouping()?;
+    let columns = task_service
+        .board(auth_state.user_id(), grouping)
+        .await
+        .map_err(|err| {
+            info!("Error getting board: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let templ = BoardPage::new(
+        auth_state.is_authed(),
+        grouping,
+        columns,
+        Globals::fetch(&session).await,
+    );
 
     Ok(HtmlTemplate(templ))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BoardMoveForm {
+    uuid: Uuid,
+    group: String,
+    #[serde(default)]
+    from: String,
+    #[serde(default)]
+    to: String,
+}
+
+/// a card dropped on another column, answers with the whole board so every
+/// column it shows up in stays right
+pub async fn post_board_move(
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+    form: Form<BoardMoveForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    let grouping = BoardQuery {
+        group: form.group.clone(),
+    }
+    .grouping()?;
+    task_service
+        .move_on_board(auth_state.user_id(), form.uuid, grouping, &form.from, &form.to)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let columns = task_service
+        .board(auth_state.user_id(), grouping)
+        .await
+        .map_err(|err| {
+            info!("Error getting board: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok(HtmlTemplate(Board::new(grouping, columns)))
+}
+
+#[derive(Debug, Clone, Template)]
+#[template(path = "task_edit.html")]
+struct EditTaskPage {
+    is_authed: bool,
+    globals: Globals,
+    task: TaskDto,
+    tasks_json: String,
+    deps_json: String,
+    tags_json: String,
+    projects: Vec<String>,
+    udas: Vec<UdaField>,
+}
+
+impl EditTaskPage {
+    fn until_input(&self) -> String {
+        self.date_input(self.task.until_at)
+    }
+
+    fn wait_input(&self) -> String {
+        self.date_input(self.task.wait_at)
+    }
+
+    fn scheduled_input(&self) -> String {
+        self.date_input(self.task.scheduled_at)
+    }
+
+    /// dates as wall clock times in a format parse_date round trips
+    fn date_input(&self, date: Option<DateTime<Utc>>) -> String {
+        date.map(|date| self.task.local(&date).format("%Y-%m-%dT%H:%M:%S").to_string())
+            .unwrap_or_default()
+    }
+
+    fn is_template(&self) -> bool {
+        self.task.status == Status::Recurring
+    }
+
+    fn due_input(&self) -> String {
+        self.date_input(self.task.due_at)
+    }
+}
+
+pub async fn get_edit_task(
+    Path(id): Path<Uuid>,
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let task = task_service.get_task(auth_state.user_id(), id).await.map_err(|err| {
+        info!("Error getting task: {:?}", err);
+        AppError::NotFound
+    })?;
+
+    let tasks_json = tasks_json(&task_service, auth_state.user_id(), Some(task.uuid)).await;
+    let deps_json = serde_json::to_string(&task.dep_uuids).map_err(|err| {
+        info!("err serialize deps {err:?}");
+        AppError::InternalServerError
+    })?;
+    let tags_json = serde_json::to_string(&task.tags.split_whitespace().collect::<Vec<_>>())
+        .map_err(|err| {
+            info!("err serialize tags {err:?}");
+            AppError::InternalServerError
+        })?;
+
+    let udas = uda_schema(&task_service, auth_state.user_id())
+        .await
+        .into_iter()
+        .map(|uda| UdaField {
+            value: task
+                .udas
+                .iter()
+                .find(|(name, _)| *name == uda.name)
+                .map(|(_, value)| uda.input(value, task.tz))
+                .unwrap_or_default(),
+            uda,
+        })
+        .collect();
+
+    let edit_page = EditTaskPage {
+        is_authed: true,
+        globals: Globals::fetch(&session).await,
+        task,
+        tasks_json,
+        deps_json,
+        tags_json,
+        projects: project_names(&task_service, auth_state.user_id()).await,
+        udas,
+    };
+
+    Ok(HtmlTemplate(edit_page))
+}
+
+pub async fn post_edit_task(
+    session: Session,
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    task_service: State<TaskService>,
+    query: Form<CreateTaskQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    task_service
+        .update_task(
+            auth_state.user_id(),
+            id,
+            (query.0, user_now(&task_service, auth_state.user_id()).await?).try_into()?,
+        )
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let task = task_service.get_task(auth_state.user_id(), id).await.map_err(|err| {
+        info!("Error getting task: {:?}", err);
+        ApiError::InternalServerError
+    })?;
+
+    let alert = Alert::new(AlertLevel::Success, format!("Task {} updated!", task.id));
+
+    let globals = Globals::fetch(&session).await.push_alert(alert);
+
+    let udas = uda_rows(&task_service, auth_state.user_id(), &task).await;
+    let task_page = TaskPage::new(true, task, udas, globals)
+        .render()
+        .map_err(|err| {
+            info!("Error rendering task: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    let task_url = HeaderValue::from_str(&format!("/task/{id}")).map_err(|err| {
+        info!("Error creating header: {err:?}");
+        ApiError::InternalServerError
+    })?;
+
+    Ok((
+        [(HeaderName::from_static("hx-replace-url"), task_url)],
+        Html(task_page),
+    ))
+}
+
 #[derive(Debug, Clone, Template, Constructor)]
 #[template(path = "partials/modal-task_done.html")]
 struct ConfirmDone {
     task: TaskDto,
 }
 pub async fn get_confirm_done(
+    auth_state: SessionAuthState,
     Path(id): Path<Uuid>,
     task_service: State<TaskService>,
 ) -> Result<impl IntoResponse, ApiError> {
     let task = task_service
-        .get_task(id)
+        .get_task(auth_state.user_id(), id)
         .await
         .map_err(|err| ApiError::BadRequest {
             message: err.to_string(),
@@ -200,25 +735,26 @@ pub async fn get_confirm_done(
 
 pub async fn post_mark_task_down(
     session: Session,
+    auth_state: SessionAuthState,
     Path(id): Path<Uuid>,
     task_service: State<TaskService>,
 ) -> Result<impl IntoResponse, ApiError> {
     task_service
-        .mark_task_done(id)
+        .mark_task_done(auth_state.user_id(), id)
         .await
         .map_err(|err| ApiError::BadRequest {
             message: err.to_string(),
         })?;
 
-    let tasks = task_service.list().await.map_err(|err| {
+    let tasks = task_service.list(auth_state.user_id(), None).await.map_err(|err| {
         info!("Error getting tasks: {:?}", err);
         ApiError::InternalServerError
     })?;
-    let alert = Alert::new(AlertLevel::Success, "Task completed!".to_owned());
+    let alert = Alert::new(AlertLevel::Success, "Task completed!".to_owned()).undoable();
 
     let globals = Globals::fetch(&session).await.push_alert(alert);
 
-    let tasks_page = TaskListPage::new(true, tasks, globals)
+    let tasks_page = TaskListPage::pending(true, tasks, String::new(), globals)
         .render()
         .map_err(|err| {
             info!("Error rendering alert: {err:?}");
@@ -234,29 +770,389 @@ pub async fn post_mark_task_down(
     ))
 }
 
-#[derive(Debug, Template, Constructor)]
-#[template(source = "<small id=due-helper>{{ message }}</small>", ext = "html")]
-struct CreateHelperText {
-    message: String,
-}
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "partials/modal-task_delete.html")]
+struct ConfirmDelete {
+    task: TaskDto,
+}
+pub async fn get_confirm_delete(
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    let task = task_service
+        .get_task(auth_state.user_id(), id)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let templ = ConfirmDelete::new(task);
+
+    Ok(HtmlTemplate(templ))
+}
+
+pub async fn post_delete_task(
+    session: Session,
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    task_service
+        .delete_task(auth_state.user_id(), id)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let tasks = task_service.list(auth_state.user_id(), None).await.map_err(|err| {
+        info!("Error getting tasks: {:?}", err);
+        ApiError::InternalServerError
+    })?;
+    let alert = Alert::new(AlertLevel::Success, "Task moved to trash!".to_owned());
+
+    let globals = Globals::fetch(&session).await.push_alert(alert);
+
+    let tasks_page = TaskListPage::pending(true, tasks, String::new(), globals)
+        .render()
+        .map_err(|err| {
+            info!("Error rendering alert: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/task"),
+        )],
+        Html(tasks_page),
+    ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "task_trash.html")]
+struct TrashPage {
+    is_authed: bool,
+    tasks: Vec<TaskDto>,
+    globals: Globals,
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "task_waiting.html")]
+struct WaitingPage {
+    is_authed: bool,
+    days: Vec<(NaiveDate, Vec<TaskDto>)>,
+    columns: Columns,
+    globals: Globals,
+}
+
+pub async fn get_waiting(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let days = task_service
+        .list_waiting(auth_state.user_id())
+        .await
+        .map_err(|err| {
+            info!("Error getting waiting tasks: {:?}", err);
+            AppError::InternalServerError
+        })?;
+    let columns = "id,wait,scheduled,due,priority,project,description,tags"
+        .parse()
+        .unwrap_or_default();
+
+    let templ = WaitingPage::new(
+        auth_state.is_authed(),
+        days,
+        columns,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+pub async fn get_trash(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let tasks = task_service
+        .list_with_status(auth_state.user_id(), Status::Deleted)
+        .await
+        .map_err(|err| {
+            info!("Error getting deleted tasks: {:?}", err);
+            AppError::InternalServerError
+        })?;
+
+    let templ = TrashPage::new(
+        auth_state.is_authed(),
+        tasks,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+pub async fn post_undelete_task(
+    session: Session,
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    task_service
+        .restore_task(auth_state.user_id(), id)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let tasks = task_service
+        .list_with_status(auth_state.user_id(), Status::Deleted)
+        .await
+        .map_err(|err| {
+            info!("Error getting deleted tasks: {:?}", err);
+            ApiError::InternalServerError
+        })?;
+    let alert = Alert::new(AlertLevel::Success, "Task restored!".to_owned());
+
+    let globals = Globals::fetch(&session).await.push_alert(alert);
+
+    let trash_page = TrashPage::new(true, tasks, globals)
+        .render()
+        .map_err(|err| {
+            info!("Error rendering trash: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/task/trash"),
+        )],
+        Html(trash_page),
+    ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "task_completed.html")]
+struct CompletedPage {
+    is_authed: bool,
+    days: Vec<(NaiveDate, Vec<TaskDto>)>,
+    globals: Globals,
+}
+
+pub async fn get_completed(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let days = task_service.list_completed(auth_state.user_id()).await.map_err(|err| {
+        info!("Error getting completed tasks: {:?}", err);
+        AppError::InternalServerError
+    })?;
+
+    let templ = CompletedPage::new(
+        auth_state.is_authed(),
+        days,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+pub async fn post_reopen_task(
+    session: Session,
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    task_service
+        .reopen_task(auth_state.user_id(), id)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let days = task_service.list_completed(auth_state.user_id()).await.map_err(|err| {
+        info!("Error getting completed tasks: {:?}", err);
+        ApiError::InternalServerError
+    })?;
+    let alert = Alert::new(AlertLevel::Success, "Task reopened!".to_owned());
+
+    let globals = Globals::fetch(&session).await.push_alert(alert);
+
+    let completed_page = CompletedPage::new(true, days, globals)
+        .render()
+        .map_err(|err| {
+            info!("Error rendering completed: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/task/completed"),
+        )],
+        Html(completed_page),
+    ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "partials/task-card.html")]
+struct TaskCard {
+    task: TaskDto,
+    detail: bool,
+    columns: Columns,
+}
 
+#[derive(Debug, Deserialize)]
+pub struct TaskCardQuery {
+    #[serde(default)]
+    detail: bool,
+    /// keep the columns of the report the card was rendered in
+    columns: Option<String>,
+    /// the list the card was rendered in, a task that moved to another one leaves it
+    listed: Option<String>,
+}
+
+impl TaskCardQuery {
+    fn columns(&self) -> Columns {
+        self.columns
+            .as_deref()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// the card alone, for pages catching up on a change made elsewhere. A task
+/// that was purged or moved to another list is taken off the page
+pub async fn get_task_card(
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    query: Query<TaskCardQuery>,
+    task_service: State<TaskService>,
+) -> Result<Response, AppError> {
+    let task = task_service
+        .find_task(auth_state.user_id(), id)
+        .await
+        .map_err(|err| {
+            info!("Error getting task card {id}: {err:?}");
+            AppError::InternalServerError
+        })?;
+
+    let moved = |task: &TaskDto| {
+        !query.detail
+            && query
+                .listed
+                .as_deref()
+                .is_some_and(|listed| listed != task.listed_as())
+    };
+    match task {
+        Some(task) if !moved(&task) => Ok(HtmlTemplate(TaskCard::new(
+            task,
+            query.detail,
+            query.columns(),
+        ))
+        .into_response()),
+        _ => Ok((
+            [(
+                HeaderName::from_static("hx-reswap"),
+                HeaderValue::from_static("delete"),
+            )],
+            "",
+        )
+            .into_response()),
+    }
+}
+
+pub async fn post_start_task(
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    query: Query<TaskCardQuery>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    let task = task_service
+        .start_task(auth_state.user_id(), id)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    Ok(HtmlTemplate(TaskCard::new(
+        task,
+        query.detail,
+        query.columns(),
+    )))
+}
+
+pub async fn post_stop_task(
+    auth_state: SessionAuthState,
+    Path(id): Path<Uuid>,
+    query: Query<TaskCardQuery>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    let task = task_service
+        .stop_task(auth_state.user_id(), id)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    Ok(HtmlTemplate(TaskCard::new(
+        task,
+        query.detail,
+        query.columns(),
+    )))
+}
+
+#[derive(Debug, Template, Constructor)]
+#[template(source = r#"<small id="{{ name }}-helper">{{ message }}</small>"#, ext = "html")]
+struct CreateHelperText {
+    /// the date input the helper sits under
+    name: &'static str,
+    message: String,
+}
+
+/// every date input asks for its own preview, under its own name
 #[derive(Debug, Deserialize)]
 pub struct DatetimeQuery {
-    due: String,
+    due: Option<String>,
+    scheduled: Option<String>,
+    wait: Option<String>,
+    until: Option<String>,
+}
+
+impl DatetimeQuery {
+    /// the input that asked and what was typed into it
+    fn field(&self) -> (&'static str, &str) {
+        [
+            ("due", &self.due),
+            ("scheduled", &self.scheduled),
+            ("wait", &self.wait),
+            ("until", &self.until),
+        ]
+        .into_iter()
+        .find_map(|(name, value)| value.as_deref().map(|value| (name, value)))
+        .unwrap_or(("due", ""))
+    }
 }
 
 pub async fn get_datetime(
+    auth_state: SessionAuthState,
     query: Query<DatetimeQuery>,
     task_service: State<TaskService>,
 ) -> Result<impl IntoResponse, impl IntoResponse> {
+    let (name, value) = query.field();
     task_service
-        .parse_datetime(&query.due)
-        .map(|due| due.format("%m-%d-%Y @ %H:%M:%S").to_string())
-        .map(|due| HtmlTemplate(CreateHelperText::new(due)))
+        .parse_datetime(auth_state.user_id(), value)
+        .await
+        .map(|date| date.format("%m-%d-%Y @ %H:%M:%S").to_string())
+        .map(|date| HtmlTemplate(CreateHelperText::new(name, date)))
         .map_err(|err| {
             (
                 StatusCode::BAD_REQUEST,
-                HtmlTemplate(CreateHelperText::new(err.to_string())),
+                HtmlTemplate(CreateHelperText::new(name, err.to_string())),
             )
         })
 }
@@ -268,11 +1164,12 @@ pub struct AnnotateQuery {
 }
 
 pub async fn patch_annotate(
+    auth_state: SessionAuthState,
     task_service: State<TaskService>,
     query: Form<AnnotateQuery>,
 ) -> Result<impl IntoResponse, Response> {
     let annotation = task_service
-        .annotate_task(query.uuid, &query.description)
+        .annotate_task(auth_state.user_id(), query.uuid, &query.description)
         .await
         .map_err(|err| {
             ApiError::BadRequest {
@@ -281,12 +1178,275 @@ pub async fn patch_annotate(
             .into_response()
         })?;
 
+    // the alert rides along out of band, the annotation list is the target
     #[derive(Template, Constructor)]
-    #[template(path = "partials/annotation.html")]
+    #[template(
+        source = r#"{% include "partials/annotation.html" %}
+<div hx-swap-oob="beforeend:#alert-container">{% include "partials/alert.html" %}</div>"#,
+        ext = "html"
+    )]
     struct AnnotateDetails {
         annotation: Annotation,
+        level: AlertLevel,
+        message: String,
+        undo: bool,
     }
-    let templ = AnnotateDetails::new(annotation);
+    let templ = AnnotateDetails::new(
+        annotation,
+        AlertLevel::Success,
+        "Annotation added!".to_owned(),
+        true,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkForm {
+    #[serde(default)]
+    uuids: Vec<Uuid>,
+    action: String,
+    #[serde(default)]
+    value: String,
+}
+
+pub async fn post_bulk(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+    form: Form<BulkForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    task_service
+        .bulk(auth_state.user_id(), &form.uuids, &form.action, &form.value)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let tasks = task_service.list(auth_state.user_id(), None).await.map_err(|err| {
+        info!("Error getting tasks: {:?}", err);
+        ApiError::InternalServerError
+    })?;
+    let alert = Alert::new(
+        AlertLevel::Success,
+        format!("Updated {} tasks!", form.uuids.len()),
+    )
+    .undoable();
+
+    let globals = Globals::fetch(&session).await.push_alert(alert);
+
+    let tasks_page = TaskListPage::pending(true, tasks, String::new(), globals)
+        .render()
+        .map_err(|err| {
+            info!("Error rendering tasks: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/task"),
+        )],
+        Html(tasks_page),
+    ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "task_undo.html")]
+struct UndoPage {
+    is_authed: bool,
+    preview: UndoPreview,
+    /// the previewed operations, posted back so only what was shown is undone
+    operations_json: String,
+    globals: Globals,
+}
+
+pub async fn get_undo(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let preview = task_service.undo_preview(auth_state.user_id()).await.map_err(|err| {
+        info!("Error getting undo operations: {err:?}");
+        AppError::InternalServerError
+    })?;
+    let operations_json = serde_json::to_string(&preview.operations).map_err(|err| {
+        info!("err serialize operations {err:?}");
+        AppError::InternalServerError
+    })?;
+
+    let templ = UndoPage::new(
+        auth_state.is_authed(),
+        preview,
+        operations_json,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UndoForm {
+    operations: String,
+}
+
+pub async fn post_undo(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+    form: Form<UndoForm>,
+) -> Result<impl IntoResponse, ApiError> {
+    let operations = serde_json::from_str(&form.operations).map_err(|err| ApiError::BadRequest {
+        message: err.to_string(),
+    })?;
+    task_service
+        .undo(auth_state.user_id(), operations)
+        .await
+        .map_err(|err| ApiError::BadRequest {
+            message: err.to_string(),
+        })?;
+
+    let tasks = task_service.list(auth_state.user_id(), None).await.map_err(|err| {
+        info!("Error getting tasks: {:?}", err);
+        ApiError::InternalServerError
+    })?;
+    let alert = Alert::new(AlertLevel::Success, "Undone!".to_owned());
+
+    let globals = Globals::fetch(&session).await.push_alert(alert);
+
+    let tasks_page = TaskListPage::pending(true, tasks, String::new(), globals)
+        .render()
+        .map_err(|err| {
+            info!("Error rendering tasks: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok((
+        [(
+            HeaderName::from_static("hx-replace-url"),
+            HeaderValue::from_static("/task"),
+        )],
+        Html(tasks_page),
+    ))
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "projects.html")]
+struct ProjectsPage {
+    is_authed: bool,
+    projects: Vec<ProjectSummary>,
+    globals: Globals,
+}
+
+pub async fn get_projects(
+    session: Session,
+    auth_state: SessionAuthState,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let projects = task_service.projects(auth_state.user_id()).await.map_err(|err| {
+        info!("Error getting projects: {err:?}");
+        AppError::InternalServerError
+    })?;
+
+    let templ = ProjectsPage::new(
+        auth_state.is_authed(),
+        projects,
+        Globals::fetch(&session).await,
+    );
 
     Ok(HtmlTemplate(templ))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    #[serde(default)]
+    q: String,
+    /// checkbox, present when ticked
+    completed: Option<String>,
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "task_search.html")]
+struct SearchPage {
+    is_authed: bool,
+    query: String,
+    completed: bool,
+    tasks: Vec<TaskDto>,
+    globals: Globals,
+}
+
+#[derive(Debug, Clone, Template, Constructor)]
+#[template(path = "partials/search-results.html")]
+struct SearchResults {
+    query: String,
+    tasks: Vec<TaskDto>,
+}
+
+pub async fn get_search(
+    session: Session,
+    auth_state: SessionAuthState,
+    query: Query<SearchQuery>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, AppError> {
+    let completed = query.completed.is_some();
+    let tasks = task_service
+        .search(auth_state.user_id(), &query.q, completed)
+        .await
+        .map_err(|err| {
+            info!("Error searching tasks: {err:?}");
+            AppError::InternalServerError
+        })?;
+
+    let templ = SearchPage::new(
+        auth_state.is_authed(),
+        query.0.q,
+        completed,
+        tasks,
+        Globals::fetch(&session).await,
+    );
+
+    Ok(HtmlTemplate(templ))
+}
+
+pub async fn get_search_results(
+    auth_state: SessionAuthState,
+    query: Query<SearchQuery>,
+    task_service: State<TaskService>,
+) -> Result<impl IntoResponse, ApiError> {
+    let tasks = task_service
+        .search(auth_state.user_id(), &query.q, query.completed.is_some())
+        .await
+        .map_err(|err| {
+            info!("Error searching tasks: {err:?}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok(HtmlTemplate(SearchResults::new(query.0.q, tasks)))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Uri;
+
+    use super::*;
+
+    fn query(uri: &str) -> DatetimeQuery {
+        Query::<DatetimeQuery>::try_from_uri(&uri.parse::<Uri>().unwrap())
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn date_helper_answers_the_field_that_asked() {
+        let wait = query("/task/date/parse?wait=tomorrow");
+        let (name, value) = wait.field();
+        assert_eq!((name, value), ("wait", "tomorrow"));
+        assert_eq!(
+            CreateHelperText::new(name, "ok".to_owned()).render().unwrap(),
+            r#"<small id="wait-helper">ok</small>"#
+        );
+
+        assert_eq!(query("/task/date/parse?until=eom").field(), ("until", "eom"));
+        assert_eq!(query("/task/date/parse?due=fri").field(), ("due", "fri"));
+    }
+}
@@ -0,0 +1,51 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// A user's own taskchampion sync server account, their tasks sync with it
+/// instead of the database shared by everyone else
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskAccount {
+    pub url: String,
+    pub client_id: Uuid,
+    pub encryption_secret: String,
+}
+
+impl TaskAccount {
+    pub fn new(url: &str, client_id: &str, encryption_secret: &str) -> Result<Self> {
+        let url = url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            bail!("The server url should start with https://");
+        }
+        let Ok(client_id) = Uuid::parse_str(client_id.trim()) else {
+            bail!("The client id should be a uuid, like taskwarrior's sync.server.client_id");
+        };
+        if encryption_secret.is_empty() {
+            bail!("The encryption secret can't be empty");
+        }
+
+        Ok(Self {
+            url: url.trim_end_matches('/').to_owned(),
+            client_id,
+            encryption_secret: encryption_secret.to_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_input() {
+        let client_id = "c3d0a5b1-2f5e-4a53-9a3b-f0e8f4a1b2c3";
+        let account =
+            TaskAccount::new(" https://tasks.example.com/ ", client_id, "s3cret").unwrap();
+        assert_eq!(account.url, "https://tasks.example.com");
+        assert_eq!(account.client_id.to_string(), client_id);
+
+        assert!(TaskAccount::new("tasks.example.com", client_id, "s3cret").is_err());
+        assert!(TaskAccount::new("https://tasks.example.com", "me", "s3cret").is_err());
+        assert!(TaskAccount::new("https://tasks.example.com", client_id, "").is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Error, Result};
+use itertools::Itertools;
+use taskchampion::Status;
+
+use super::task::TaskDto;
+
+/// What the columns of the board are
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Grouping {
+    #[default]
+    State,
+    Priority,
+    Project,
+    Tag,
+}
+
+impl Grouping {
+    pub const ALL: [Grouping; 4] = [
+        Grouping::State,
+        Grouping::Priority,
+        Grouping::Project,
+        Grouping::Tag,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Grouping::State => "State",
+            Grouping::Priority => "Priority",
+            Grouping::Project => "Project",
+            Grouping::Tag => "Tag",
+        }
+    }
+}
+
+impl FromStr for Grouping {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "state" => Ok(Grouping::State),
+            "priority" => Ok(Grouping::Priority),
+            "project" => Ok(Grouping::Project),
+            "tag" => Ok(Grouping::Tag),
+            _ => bail!("Unknown board grouping '{s}'"),
+        }
+    }
+}
+
+impl fmt::Display for Grouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Grouping::State => "state",
+            Grouping::Priority => "priority",
+            Grouping::Project => "project",
+            Grouping::Tag => "tag",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Where a task is in its life, the columns of the state board
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskState {
+    Pending,
+    Active,
+    Waiting,
+    Done,
+}
+
+impl TaskState {
+    const ALL: [TaskState; 4] = [
+        TaskState::Pending,
+        TaskState::Active,
+        TaskState::Waiting,
+        TaskState::Done,
+    ];
+
+    pub fn of(task: &TaskDto) -> Self {
+        if task.status == Status::Completed {
+            TaskState::Done
+        } else if task.is_active {
+            TaskState::Active
+        } else if task.wait.is_some() {
+            TaskState::Waiting
+        } else {
+            TaskState::Pending
+        }
+    }
+}
+
+impl FromStr for TaskState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pending" => Ok(TaskState::Pending),
+            "active" => Ok(TaskState::Active),
+            "waiting" => Ok(TaskState::Waiting),
+            "done" => Ok(TaskState::Done),
+            _ => bail!("Unknown state '{s}'"),
+        }
+    }
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TaskState::Pending => "pending",
+            TaskState::Active => "active",
+            TaskState::Waiting => "waiting",
+            TaskState::Done => "done",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A board column, `key` is the value a dropped task takes on, empty for none
+#[derive(Debug, Clone)]
+pub struct BoardColumn {
+    pub key: String,
+    pub label: String,
+    /// waiting needs a date, tasks only get there from the edit page
+    pub droppable: bool,
+    pub tasks: Vec<TaskDto>,
+}
+
+impl BoardColumn {
+    fn new(key: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            label: label.into(),
+            droppable: true,
+            tasks: vec![],
+        }
+    }
+
+    /// tasks in columns keyed by `keys`, a task with several tags shows up under each.
+    /// Columns in `fixed` are always there, others appear in name order once a task has them.
+    fn group(
+        fixed: Vec<BoardColumn>,
+        tasks: Vec<TaskDto>,
+        keys: impl Fn(&TaskDto) -> Vec<String>,
+    ) -> Vec<BoardColumn> {
+        let mut columns = fixed;
+        for task in tasks {
+            for key in keys(&task) {
+                match columns.iter_mut().find(|column| column.key == key) {
+                    Some(column) => column.tasks.push(task.clone()),
+                    None => {
+                        let mut column = BoardColumn::new(key.clone(), key);
+                        column.tasks.push(task.clone());
+                        columns.push(column);
+                    }
+                }
+            }
+        }
+        // the no-value column stays last
+        let (named, none): (Vec<_>, Vec<_>) =
+            columns.into_iter().partition(|column| !column.key.is_empty());
+        named
+            .into_iter()
+            .sorted_by(|a, b| a.key.cmp(&b.key))
+            .chain(none)
+            .collect()
+    }
+}
+
+impl Grouping {
+    /// `tasks` in columns, keeping the order they came in
+    pub fn columns(self, tasks: Vec<TaskDto>) -> Vec<BoardColumn> {
+        match self {
+            Grouping::State => {
+                let mut columns: Vec<BoardColumn> = TaskState::ALL
+                    .iter()
+                    .map(|state| {
+                        let mut column = BoardColumn::new(state.to_string(), state.to_string());
+                        column.droppable = *state != TaskState::Waiting;
+                        column
+                    })
+                    .collect();
+                for task in tasks {
+                    let state = TaskState::of(&task);
+                    if let Some(column) = columns.iter_mut().find(|c| c.key == state.to_string()) {
+                        column.tasks.push(task);
+                    }
+                }
+                columns
+            }
+            Grouping::Priority => {
+                let mut columns = vec![
+                    BoardColumn::new("h", "High"),
+                    BoardColumn::new("m", "Medium"),
+                    BoardColumn::new("l", "Low"),
+                    BoardColumn::new("", "None"),
+                ];
+                for task in tasks {
+                    let key = task.priority.to_lowercase();
+                    if let Some(column) = columns.iter_mut().find(|c| c.key == key) {
+                        column.tasks.push(task);
+                    }
+                }
+                columns
+            }
+            Grouping::Project => BoardColumn::group(
+                vec![BoardColumn::new("", "No project")],
+                tasks,
+                |task| vec![task.project.clone()],
+            ),
+            Grouping::Tag => {
+                BoardColumn::group(vec![BoardColumn::new("", "No tags")], tasks, |task| {
+                    match task.tags.split_whitespace().map(str::to_owned).collect_vec() {
+                        tags if tags.is_empty() => vec![String::new()],
+                        tags => tags,
+                    }
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::testing::task_dto;
+    use taskchampion::Tag;
+
+    fn keys(columns: &[BoardColumn]) -> Vec<(&str, Vec<&str>)> {
+        columns
+            .iter()
+            .map(|column| {
+                let tasks = column.tasks.iter().map(|t| t.description.as_str()).collect();
+                (column.key.as_str(), tasks)
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn groups_tasks_into_columns() {
+        let tag = |name: &str| Tag::try_from(name).unwrap();
+        let plain = task_dto("plain", |_, _| {}).await;
+        let home = task_dto("home", |t, ops| {
+            t.set_priority("H".to_owned(), ops).unwrap();
+            t.set_user_defined_attribute("project", "home", ops).unwrap();
+            t.add_tag(&tag("next"), ops).unwrap();
+            t.add_tag(&tag("errand"), ops).unwrap();
+        })
+        .await;
+        let started = task_dto("started", |t, ops| t.start(ops).unwrap()).await;
+        let done = task_dto("done", |t, ops| t.done(ops).unwrap()).await;
+        let tasks = vec![plain, home, started, done];
+
+        let state = Grouping::State.columns(tasks.clone());
+        assert_eq!(
+            keys(&state),
+            vec![
+                ("pending", vec!["plain", "home"]),
+                ("active", vec!["started"]),
+                ("waiting", vec![]),
+                ("done", vec!["done"]),
+            ]
+        );
+        assert!(!state[2].droppable);
+
+        let priority = Grouping::Priority.columns(tasks.clone());
+        assert_eq!(priority[0].tasks.len(), 1);
+        assert_eq!(priority[3].tasks.len(), 3);
+
+        assert_eq!(
+            keys(&Grouping::Project.columns(tasks.clone())),
+            vec![("home", vec!["home"]), ("", vec!["plain", "started", "done"])]
+        );
+        assert_eq!(
+            keys(&Grouping::Tag.columns(tasks)),
+            vec![
+                ("errand", vec!["home"]),
+                ("next", vec!["home"]),
+                ("", vec!["plain", "started", "done"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_groupings() {
+        assert_eq!("tag".parse::<Grouping>().unwrap(), Grouping::Tag);
+        assert_eq!(Grouping::default().to_string(), "state");
+        assert!("colour".parse::<Grouping>().is_err());
+        assert_eq!("done".parse::<TaskState>().unwrap(), TaskState::Done);
+    }
+}
@@ -0,0 +1,277 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{bail, Error, Result};
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+
+use super::task::TaskDto;
+use crate::infra::datetime::{month_end, month_start, week_start};
+
+/// How much of the calendar is shown at once
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CalendarView {
+    Day,
+    #[default]
+    Week,
+    Month,
+}
+
+impl CalendarView {
+    pub const ALL: [CalendarView; 3] = [CalendarView::Day, CalendarView::Week, CalendarView::Month];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CalendarView::Day => "Day",
+            CalendarView::Week => "Week",
+            CalendarView::Month => "Month",
+        }
+    }
+}
+
+impl FromStr for CalendarView {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "day" => Ok(CalendarView::Day),
+            "week" => Ok(CalendarView::Week),
+            "month" => Ok(CalendarView::Month),
+            _ => bail!("Unknown calendar view '{s}'"),
+        }
+    }
+}
+
+impl fmt::Display for CalendarView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            CalendarView::Day => "day",
+            CalendarView::Week => "week",
+            CalendarView::Month => "month",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Which of a task's dates put it on a day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DateKind {
+    Due,
+    Scheduled,
+    /// the day a waiting task shows up again
+    Wait,
+}
+
+impl fmt::Display for DateKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            DateKind::Due => "due",
+            DateKind::Scheduled => "scheduled",
+            DateKind::Wait => "wait",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarEntry {
+    pub kind: DateKind,
+    /// wall clock time in the user's timezone
+    pub time: NaiveTime,
+    pub task: TaskDto,
+}
+
+impl CalendarEntry {
+    /// dates without a time of day are stored as midnight
+    pub fn all_day(&self) -> bool {
+        self.time == NaiveTime::MIN
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    /// false for the days of other months that fill up a month grid
+    pub in_range: bool,
+    pub today: bool,
+    pub entries: Vec<CalendarEntry>,
+}
+
+/// Days around `anchor` with the tasks that fall on them
+#[derive(Debug, Clone)]
+pub struct Calendar {
+    pub view: CalendarView,
+    pub anchor: NaiveDate,
+    pub days: Vec<CalendarDay>,
+}
+
+impl Calendar {
+    /// `local` turns stored dates into wall clock times of the user's timezone,
+    /// so a task lands on the day the user sees it on
+    pub fn new(
+        view: CalendarView,
+        anchor: NaiveDate,
+        today: NaiveDate,
+        weekstart: Weekday,
+        tasks: Vec<TaskDto>,
+        local: impl Fn(DateTime<Utc>) -> NaiveDateTime,
+    ) -> Self {
+        let (first, last, range) = match view {
+            CalendarView::Day => (anchor, anchor, (anchor, anchor)),
+            CalendarView::Week => {
+                let start = week_start(anchor, weekstart);
+                (start, start + Duration::days(6), (start, start + Duration::days(6)))
+            }
+            // whole weeks around the month so the grid lines up
+            CalendarView::Month => {
+                let (start, end) = (month_start(anchor, 0), month_end(anchor, 0));
+                let first = week_start(start, weekstart);
+                let last = week_start(end, weekstart) + Duration::days(6);
+                (first, last, (start, end))
+            }
+        };
+
+        let mut days: Vec<CalendarDay> = first
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .map(|date| CalendarDay {
+                date,
+                in_range: range.0 <= date && date <= range.1,
+                today: date == today,
+                entries: vec![],
+            })
+            .collect();
+
+        for task in tasks {
+            let dates = [
+                (DateKind::Due, task.due_at),
+                (DateKind::Scheduled, task.scheduled_at),
+                (DateKind::Wait, task.wait_at.filter(|_| task.wait.is_some())),
+            ];
+            for (kind, date) in dates {
+                let Some(date) = date.map(&local) else {
+                    continue;
+                };
+                if let Some(day) = days.iter_mut().find(|day| day.date == date.date()) {
+                    day.entries.push(CalendarEntry {
+                        kind,
+                        time: date.time(),
+                        task: task.clone(),
+                    });
+                }
+            }
+        }
+        for day in days.iter_mut() {
+            day.entries.sort_by_key(|entry| entry.time);
+        }
+
+        Self { view, anchor, days }
+    }
+
+    pub fn title(&self) -> String {
+        match self.view {
+            CalendarView::Day => self.anchor.format("%A %-d %B %Y").to_string(),
+            CalendarView::Week => {
+                let first = self.days.first().map_or(self.anchor, |day| day.date);
+                format!("Week of {}", first.format("%-d %B %Y"))
+            }
+            CalendarView::Month => self.anchor.format("%B %Y").to_string(),
+        }
+    }
+
+    /// the anchor one view back
+    pub fn previous(&self) -> NaiveDate {
+        match self.view {
+            CalendarView::Day => self.anchor - Duration::days(1),
+            CalendarView::Week => self.anchor - Duration::days(7),
+            CalendarView::Month => month_start(self.anchor, -1),
+        }
+    }
+
+    /// the anchor one view ahead
+    pub fn next(&self) -> NaiveDate {
+        match self.view {
+            CalendarView::Day => self.anchor + Duration::days(1),
+            CalendarView::Week => self.anchor + Duration::days(7),
+            CalendarView::Month => month_start(self.anchor, 1),
+        }
+    }
+
+    pub fn weeks(&self) -> Vec<&[CalendarDay]> {
+        self.days.chunks(7).collect()
+    }
+
+    /// short day names for the grid header, starting on the week start
+    pub fn weekdays(&self) -> Vec<String> {
+        self.days
+            .iter()
+            .take(7)
+            .map(|day| day.date.format("%a").to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::testing::task_dto;
+
+    fn date(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, d).unwrap()
+    }
+
+    #[test]
+    fn ranges_follow_view_and_weekstart() {
+        let tue = date(5);
+        let week = Calendar::new(CalendarView::Week, tue, tue, Weekday::Sun, vec![], |d| {
+            d.naive_utc()
+        });
+        assert_eq!(week.days.first().unwrap().date, date(3));
+        assert_eq!(week.days.len(), 7);
+        assert_eq!(week.weekdays()[0], "Sun");
+
+        // march 2024 starts on a friday and ends on a sunday
+        let month = Calendar::new(CalendarView::Month, tue, tue, Weekday::Mon, vec![], |d| {
+            d.naive_utc()
+        });
+        assert_eq!(
+            month.days.first().unwrap().date,
+            NaiveDate::from_ymd_opt(2024, 2, 26).unwrap()
+        );
+        assert_eq!(month.days.last().unwrap().date, date(31));
+        assert_eq!(month.weeks().len(), 5);
+        assert!(!month.days[0].in_range);
+        assert_eq!(month.next(), NaiveDate::from_ymd_opt(2024, 4, 1).unwrap());
+        assert_eq!(month.previous(), NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+    }
+
+    #[tokio::test]
+    async fn tasks_land_on_local_days() {
+        // 23:30 UTC on the 5th is already the 6th an hour east
+        let due = date(5).and_hms_opt(23, 30, 0).unwrap().and_utc();
+        let late = task_dto("late", |t, ops| t.set_due(Some(due), ops).unwrap()).await;
+        let at = date(6).and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let scheduled = task_dto("scheduled", |t, ops| {
+            t.set_timestamp("scheduled", Some(at), ops).unwrap()
+        })
+        .await;
+
+        let calendar = Calendar::new(
+            CalendarView::Week,
+            date(5),
+            date(5),
+            Weekday::Mon,
+            vec![late, scheduled],
+            |d| d.naive_utc() + Duration::hours(1),
+        );
+        let sixth = calendar.days.iter().find(|day| day.date == date(6)).unwrap();
+        let entries: Vec<_> = sixth
+            .entries
+            .iter()
+            .map(|entry| (entry.kind, entry.task.description.as_str()))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![(DateKind::Due, "late"), (DateKind::Scheduled, "scheduled")]
+        );
+        assert!(calendar.days[1].today);
+    }
+}
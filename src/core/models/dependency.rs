@@ -0,0 +1,123 @@
+use std::{collections::HashMap, iter};
+
+use uuid::Uuid;
+
+use super::task::TaskDto;
+
+/// Which task depends on which, built from every task so paths through
+/// completed or deleted tasks still count
+#[derive(Debug, Default)]
+pub struct DependencyGraph {
+    /// task → the tasks it depends on
+    deps: HashMap<Uuid, Vec<Uuid>>,
+    /// task → the tasks depending on it
+    dependents: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl DependencyGraph {
+    pub fn new(tasks: impl IntoIterator<Item = (Uuid, Vec<Uuid>)>) -> Self {
+        let mut graph = Self::default();
+        for (task, deps) in tasks {
+            for dep in deps.iter() {
+                graph.dependents.entry(*dep).or_default().push(task);
+            }
+            graph.deps.insert(task, deps);
+        }
+        graph
+    }
+
+    /// the cycle `task` depending on `dep` would close, `task` first and last
+    pub fn cycle_with(&self, task: Uuid, dep: Uuid) -> Option<Vec<Uuid>> {
+        let path = self.path(dep, task)?;
+        Some(iter::once(task).chain(path).collect())
+    }
+
+    /// a chain of dependencies leading from `from` to `to`, both included
+    fn path(&self, from: Uuid, to: Uuid) -> Option<Vec<Uuid>> {
+        let mut stack = vec![vec![from]];
+        let mut seen = vec![from];
+        while let Some(path) = stack.pop() {
+            let last = *path.last().expect("paths are never empty");
+            if last == to {
+                return Some(path);
+            }
+            for dep in self.deps.get(&last).into_iter().flatten() {
+                if !seen.contains(dep) {
+                    seen.push(*dep);
+                    stack.push(path.iter().copied().chain([*dep]).collect());
+                }
+            }
+        }
+        None
+    }
+
+    /// what `task` waits on, depth first as (depth, uuid) with direct deps at depth 0
+    pub fn upstream(&self, task: Uuid) -> Vec<(usize, Uuid)> {
+        Self::flatten(&self.deps, task)
+    }
+
+    /// what waits on `task`, depth first as (depth, uuid)
+    pub fn downstream(&self, task: Uuid) -> Vec<(usize, Uuid)> {
+        Self::flatten(&self.dependents, task)
+    }
+
+    fn flatten(edges: &HashMap<Uuid, Vec<Uuid>>, root: Uuid) -> Vec<(usize, Uuid)> {
+        fn walk(
+            edges: &HashMap<Uuid, Vec<Uuid>>,
+            path: &mut Vec<Uuid>,
+            nodes: &mut Vec<(usize, Uuid)>,
+        ) {
+            let last = *path.last().expect("paths are never empty");
+            for next in edges.get(&last).into_iter().flatten() {
+                // a cycle synced in from another replica ends the branch
+                if path.contains(next) {
+                    continue;
+                }
+                nodes.push((path.len() - 1, *next));
+                path.push(*next);
+                walk(edges, path, nodes);
+                path.pop();
+            }
+        }
+
+        let mut nodes = vec![];
+        walk(edges, &mut vec![root], &mut nodes);
+        nodes
+    }
+}
+
+/// a task with what it waits on and what waits on it, as (depth, task)
+#[derive(Debug, Clone)]
+pub struct TaskDependencies {
+    pub task: TaskDto,
+    pub upstream: Vec<(usize, TaskDto)>,
+    pub downstream: Vec<(usize, TaskDto)>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_cycles_and_walks_both_ways() {
+        let [a, b, c, d] = [(); 4].map(|_| Uuid::new_v4());
+        // a waits on b, b waits on c and d
+        let graph = DependencyGraph::new([(a, vec![b]), (b, vec![c, d]), (c, vec![]), (d, vec![])]);
+
+        assert_eq!(graph.cycle_with(c, a), Some(vec![c, a, b, c]));
+        assert_eq!(graph.cycle_with(a, a), Some(vec![a, a]));
+        assert_eq!(graph.cycle_with(d, c), None);
+
+        assert_eq!(graph.upstream(a), vec![(0, b), (1, c), (1, d)]);
+        assert_eq!(graph.downstream(d), vec![(0, b), (1, a)]);
+        assert!(graph.downstream(a).is_empty());
+    }
+
+    #[test]
+    fn existing_cycles_end_the_walk() {
+        let [a, b] = [(); 2].map(|_| Uuid::new_v4());
+        let graph = DependencyGraph::new([(a, vec![b]), (b, vec![a])]);
+
+        assert_eq!(graph.upstream(a), vec![(0, b)]);
+    }
+}
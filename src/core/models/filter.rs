@@ -0,0 +1,883 @@
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Datelike, Duration};
+use chrono_tz::Tz;
+use taskchampion::{Status, Tag, Task};
+
+use crate::infra::datetime::{from_wall_clock, parse_date};
+
+/// A parsed taskwarrior filter expression, e.g. `project:work +next due.before:eow -WAITING`.
+///
+/// Terms next to each other are joined with an implicit `and`; `and`, `or` and
+/// parentheses work as in taskwarrior. Dates are resolved once at parse time
+/// relative to `now`, and read as wall clock times in its timezone, the user's.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Option<Expr>,
+    now: DateTime<Tz>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Tag {
+        tag: Tag,
+        include: bool,
+    },
+    Virtual {
+        tag: VirtualTag,
+        include: bool,
+    },
+    Attr {
+        attr: Attr,
+        modifier: Modifier,
+        value: Value,
+    },
+    /// inclusive working set id ranges, `1,3-5`
+    Ids(Vec<(usize, usize)>),
+    UuidPrefix(String),
+    Word(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum VirtualTag {
+    /// tags taskchampion already knows about, ACTIVE, WAITING, BLOCKED...
+    Synthetic(Tag),
+    Due,
+    DueToday,
+    Tomorrow,
+    Yesterday,
+    Week,
+    Month,
+    Quarter,
+    Year,
+    Overdue,
+    Tagged,
+    Annotated,
+    Project,
+    Priority,
+    Scheduled,
+    Until,
+    Ready,
+    Parent,
+    Child,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Attr {
+    Description,
+    Project,
+    Priority,
+    Status,
+    Uuid,
+    Id,
+    Tags,
+    Depends,
+    Date(&'static str),
+    Uda(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Modifier {
+    Default,
+    Is,
+    Isnt,
+    Has,
+    Hasnt,
+    Startswith,
+    Endswith,
+    Word,
+    Noword,
+    Before,
+    After,
+    By,
+    None,
+    Any,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Empty,
+    Text(String),
+    Date(DateTime<Tz>),
+    Number(usize),
+}
+
+const ATTRIBUTES: &[&str] = &[
+    "description",
+    "project",
+    "priority",
+    "status",
+    "uuid",
+    "id",
+    "tags",
+    "depends",
+    "due",
+    "wait",
+    "scheduled",
+    "until",
+    "entry",
+    "end",
+    "start",
+    "modified",
+];
+
+/// days ahead a task counts as +DUE, taskwarrior's rc.due default
+const DUE_DAYS: i64 = 7;
+
+impl Filter {
+    pub fn parse(input: &str, now: DateTime<Tz>) -> Result<Self> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            now,
+        };
+
+        let expr = if parser.tokens.is_empty() {
+            None
+        } else {
+            let expr = parser.parse_or()?;
+            if let Some(token) = parser.peek() {
+                bail!("Unexpected '{token}' in filter");
+            }
+            Some(expr)
+        };
+
+        Ok(Self { expr, now })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expr.is_none()
+    }
+
+    /// whether the filter can match tasks outside the pending list,
+    /// e.g. `status:completed` or `+WAITING`
+    pub fn needs_all_tasks(&self) -> bool {
+        self.expr.as_ref().is_some_and(Expr::needs_all_tasks)
+    }
+
+    /// whether deleted tasks are asked for, `status:deleted` or `+DELETED`. Like
+    /// taskwarrior's reports they are left out of every other filter
+    pub fn includes_deleted(&self) -> bool {
+        self.expr.as_ref().is_some_and(Expr::includes_deleted)
+    }
+
+    /// `id` is the working set id, 0 when the task has none
+    pub fn matches(&self, id: usize, task: &Task) -> bool {
+        self.expr
+            .as_ref()
+            .is_none_or(|expr| expr.matches(id, task, &self.now))
+    }
+}
+
+impl Expr {
+    fn needs_all_tasks(&self) -> bool {
+        match self {
+            Expr::And(lhs, rhs) | Expr::Or(lhs, rhs) => {
+                lhs.needs_all_tasks() || rhs.needs_all_tasks()
+            }
+            // `status.isnt:completed` is met by the pending list already
+            Expr::Attr {
+                attr: Attr::Status,
+                modifier,
+                value: Value::Text(status),
+            } => match modifier {
+                Modifier::Isnt | Modifier::Hasnt => status == "pending",
+                _ => status != "pending",
+            },
+            // only waiting tasks have a wait ahead and only closed ones an end,
+            // asking for neither is met by the pending list
+            Expr::Attr {
+                attr: Attr::Date("wait" | "end"),
+                modifier,
+                value,
+            } => match (modifier, value) {
+                (Modifier::None, _) => false,
+                (Modifier::Isnt | Modifier::Any, Value::Empty) => true,
+                (_, Value::Empty) => false,
+                _ => true,
+            },
+            Expr::Virtual {
+                tag,
+                include: true,
+            } => match tag {
+                VirtualTag::Synthetic(tag) => {
+                    matches!(tag.as_ref(), "WAITING" | "COMPLETED" | "DELETED")
+                }
+                VirtualTag::Parent => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn includes_deleted(&self) -> bool {
+        match self {
+            Expr::And(lhs, rhs) | Expr::Or(lhs, rhs) => {
+                lhs.includes_deleted() || rhs.includes_deleted()
+            }
+            Expr::Attr {
+                attr: Attr::Status,
+                modifier,
+                value: Value::Text(status),
+            } => status == "deleted" && !matches!(modifier, Modifier::Isnt | Modifier::Hasnt),
+            Expr::Virtual {
+                tag: VirtualTag::Synthetic(tag),
+                include: true,
+            } => tag.as_ref() == "DELETED",
+            _ => false,
+        }
+    }
+
+    fn matches(&self, id: usize, task: &Task, now: &DateTime<Tz>) -> bool {
+        match self {
+            Expr::And(lhs, rhs) => lhs.matches(id, task, now) && rhs.matches(id, task, now),
+            Expr::Or(lhs, rhs) => lhs.matches(id, task, now) || rhs.matches(id, task, now),
+            Expr::Tag { tag, include } => task.has_tag(tag) == *include,
+            Expr::Virtual { tag, include } => tag.matches(task, now) == *include,
+            Expr::Attr {
+                attr,
+                modifier,
+                value,
+            } => attr_matches(attr, *modifier, value, id, task),
+            Expr::Ids(ranges) => {
+                id != 0 && ranges.iter().any(|(from, to)| (*from..=*to).contains(&id))
+            }
+            Expr::UuidPrefix(prefix) => task.get_uuid().to_string().starts_with(prefix),
+            Expr::Word(word) => task.get_description().to_lowercase().contains(word),
+        }
+    }
+}
+
+impl VirtualTag {
+    fn from_name(name: &str) -> Result<Self> {
+        let tag = match name {
+            "DUE" => VirtualTag::Due,
+            "DUETODAY" | "TODAY" => VirtualTag::DueToday,
+            "TOMORROW" => VirtualTag::Tomorrow,
+            "YESTERDAY" => VirtualTag::Yesterday,
+            "WEEK" => VirtualTag::Week,
+            "MONTH" => VirtualTag::Month,
+            "QUARTER" => VirtualTag::Quarter,
+            "YEAR" => VirtualTag::Year,
+            "OVERDUE" => VirtualTag::Overdue,
+            "TAGGED" => VirtualTag::Tagged,
+            "ANNOTATED" => VirtualTag::Annotated,
+            "PROJECT" => VirtualTag::Project,
+            "PRIORITY" => VirtualTag::Priority,
+            "SCHEDULED" => VirtualTag::Scheduled,
+            "UNTIL" => VirtualTag::Until,
+            "READY" => VirtualTag::Ready,
+            "PARENT" => VirtualTag::Parent,
+            "CHILD" => VirtualTag::Child,
+            _ => Tag::try_from(name)
+                .map(VirtualTag::Synthetic)
+                .map_err(|_| anyhow!("Unknown virtual tag {name}"))?,
+        };
+        Ok(tag)
+    }
+
+    fn matches(&self, task: &Task, now: &DateTime<Tz>) -> bool {
+        let today = now.date_naive();
+        let due = task.get_due();
+        let due_day = due.map(|due| due.with_timezone(&now.timezone()).date_naive());
+        match self {
+            VirtualTag::Synthetic(tag) => task.has_tag(tag),
+            VirtualTag::Due => due.is_some_and(|due| due <= *now + Duration::days(DUE_DAYS)),
+            VirtualTag::DueToday => due_day == Some(today),
+            VirtualTag::Tomorrow => due_day == today.succ_opt(),
+            VirtualTag::Yesterday => due_day == today.pred_opt(),
+            VirtualTag::Week => due_day.is_some_and(|day| day.iso_week() == today.iso_week()),
+            VirtualTag::Month => due_day
+                .is_some_and(|day| day.year() == today.year() && day.month() == today.month()),
+            VirtualTag::Quarter => due_day.is_some_and(|day| {
+                day.year() == today.year() && day.month0() / 3 == today.month0() / 3
+            }),
+            VirtualTag::Year => due_day.is_some_and(|day| day.year() == today.year()),
+            VirtualTag::Overdue => {
+                task.get_status() == Status::Pending && due.is_some_and(|due| due < *now)
+            }
+            VirtualTag::Tagged => task.get_tags().any(|tag| tag.is_user()),
+            VirtualTag::Annotated => task.get_annotations().next().is_some(),
+            VirtualTag::Project => task.get_value("project").is_some(),
+            VirtualTag::Priority => !task.get_priority().is_empty(),
+            VirtualTag::Scheduled => task.get_timestamp("scheduled").is_some(),
+            VirtualTag::Until => task.get_timestamp("until").is_some(),
+            VirtualTag::Ready => {
+                task.get_status() == Status::Pending
+                    && !task.is_blocked()
+                    && !task.is_waiting()
+                    && task
+                        .get_timestamp("scheduled")
+                        .is_none_or(|scheduled| scheduled <= *now)
+            }
+            VirtualTag::Parent => task.get_status() == Status::Recurring,
+            VirtualTag::Child => task.get_value("parent").is_some(),
+        }
+    }
+}
+
+impl Attr {
+    /// exact names first, then an unambiguous prefix (`pro` for project),
+    /// anything else is treated as a user defined attribute
+    fn from_name(name: &str) -> Result<Self> {
+        let name = name.to_lowercase();
+        let full = match ATTRIBUTES.iter().find(|attr| **attr == name) {
+            Some(attr) => Some(*attr),
+            None => {
+                let candidates: Vec<_> = ATTRIBUTES
+                    .iter()
+                    .filter(|attr| attr.starts_with(&name))
+                    .collect();
+                match candidates.as_slice() {
+                    [] => None,
+                    [attr] => Some(**attr),
+                    _ => bail!("Ambiguous attribute '{name}'"),
+                }
+            }
+        };
+
+        let attr = match full {
+            Some("description") => Attr::Description,
+            Some("project") => Attr::Project,
+            Some("priority") => Attr::Priority,
+            Some("status") => Attr::Status,
+            Some("uuid") => Attr::Uuid,
+            Some("id") => Attr::Id,
+            Some("tags") => Attr::Tags,
+            Some("depends") => Attr::Depends,
+            Some(date) => Attr::Date(date),
+            None => Attr::Uda(name),
+        };
+        Ok(attr)
+    }
+}
+
+impl Modifier {
+    fn from_name(name: &str) -> Result<Self> {
+        let modifier = match name.to_lowercase().as_str() {
+            "is" | "equals" => Modifier::Is,
+            "isnt" | "not" => Modifier::Isnt,
+            "has" | "contains" => Modifier::Has,
+            "hasnt" => Modifier::Hasnt,
+            "startswith" | "left" => Modifier::Startswith,
+            "endswith" | "right" => Modifier::Endswith,
+            "word" => Modifier::Word,
+            "noword" => Modifier::Noword,
+            "before" | "under" | "below" => Modifier::Before,
+            "after" | "over" | "above" => Modifier::After,
+            "by" => Modifier::By,
+            "none" => Modifier::None,
+            "any" => Modifier::Any,
+            _ => bail!("Unknown modifier '{name}'"),
+        };
+        Ok(modifier)
+    }
+
+    fn is_ordering(&self) -> bool {
+        matches!(self, Modifier::Before | Modifier::After | Modifier::By)
+    }
+}
+
+fn attr_matches(attr: &Attr, modifier: Modifier, value: &Value, id: usize, task: &Task) -> bool {
+    // presence checks work the same for every attribute, `project:` means no project
+    let present = match attr {
+        Attr::Tags => task.get_tags().any(|tag| tag.is_user()),
+        Attr::Depends => task.get_dependencies().next().is_some(),
+        Attr::Id => id != 0,
+        Attr::Date(prop) => task.get_timestamp(prop).is_some(),
+        _ => text_value(attr, task).is_some(),
+    };
+    match (modifier, value) {
+        (Modifier::None, _) => return !present,
+        (Modifier::Any, _) => return present,
+        (Modifier::Isnt, Value::Empty) => return present,
+        (_, Value::Empty) => return !present,
+        _ => {}
+    }
+
+    match (attr, value) {
+        (Attr::Date(prop), Value::Date(date)) => {
+            let Some(actual) = task.get_timestamp(prop) else {
+                return modifier == Modifier::Isnt;
+            };
+            let same_day = actual.with_timezone(&date.timezone()).date_naive() == date.date_naive();
+            match modifier {
+                Modifier::Before => actual < *date,
+                Modifier::After => actual > *date,
+                Modifier::By => actual <= *date,
+                Modifier::Isnt => !same_day,
+                _ => same_day,
+            }
+        }
+        (Attr::Id, Value::Number(n)) => match modifier {
+            Modifier::Before => id != 0 && id < *n,
+            Modifier::After => id > *n,
+            Modifier::By => id != 0 && id <= *n,
+            Modifier::Isnt => id != *n,
+            _ => id == *n,
+        },
+        (Attr::Tags, Value::Text(tag)) => {
+            let has = task
+                .get_tags()
+                .any(|t| t.is_user() && t.as_ref().eq_ignore_ascii_case(tag));
+            match modifier {
+                Modifier::Isnt | Modifier::Hasnt => !has,
+                _ => has,
+            }
+        }
+        (Attr::Depends, Value::Text(prefix)) => {
+            let has = task
+                .get_dependencies()
+                .any(|uuid| uuid.to_string().starts_with(prefix));
+            match modifier {
+                Modifier::Isnt | Modifier::Hasnt => !has,
+                _ => has,
+            }
+        }
+        (Attr::Status, Value::Text(status)) if status == "waiting" => {
+            let waiting = task.get_status() == Status::Pending && task.is_waiting();
+            waiting == (modifier != Modifier::Isnt)
+        }
+        (_, Value::Text(expected)) => {
+            let actual = text_value(attr, task).map(|actual| actual.to_lowercase());
+            text_matches(attr, modifier, actual.as_deref(), expected)
+        }
+        _ => false,
+    }
+}
+
+fn text_value(attr: &Attr, task: &Task) -> Option<String> {
+    match attr {
+        Attr::Description => Some(task.get_description().to_owned()),
+        Attr::Project => task.get_value("project").map(str::to_owned),
+        Attr::Priority => Some(task.get_priority().to_owned()).filter(|pri| !pri.is_empty()),
+        Attr::Status => task.get_value("status").map(str::to_owned),
+        Attr::Uuid => Some(task.get_uuid().to_string()),
+        Attr::Uda(key) => task.get_user_defined_attribute(key).map(str::to_owned),
+        _ => None,
+    }
+}
+
+fn text_matches(attr: &Attr, modifier: Modifier, actual: Option<&str>, expected: &str) -> bool {
+    let Some(actual) = actual else {
+        return matches!(modifier, Modifier::Isnt | Modifier::Hasnt | Modifier::Noword);
+    };
+    let has_word = || actual.split_whitespace().any(|word| word == expected);
+    match modifier {
+        Modifier::Is => actual == expected,
+        Modifier::Isnt => actual != expected,
+        Modifier::Has => actual.contains(expected),
+        Modifier::Hasnt => !actual.contains(expected),
+        Modifier::Startswith => actual.starts_with(expected),
+        Modifier::Endswith => actual.ends_with(expected),
+        Modifier::Word => has_word(),
+        Modifier::Noword => !has_word(),
+        _ => match attr {
+            // project:work also matches work.home
+            Attr::Project => {
+                actual == expected
+                    || actual
+                        .strip_prefix(expected)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            }
+            Attr::Description => actual.contains(expected),
+            Attr::Uuid => actual.starts_with(expected),
+            _ => actual == expected,
+        },
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+
+    for c in input.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            _ if quoted => current.push(c),
+            '(' | ')' => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                tokens.push(c.to_string());
+            }
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        bail!("Unterminated quote in filter");
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+    now: DateTime<Tz>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn peek_operator(&self, op: &str) -> bool {
+        self.peek().is_some_and(|token| token.eq_ignore_ascii_case(op))
+    }
+
+    fn parse_or(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_and()?;
+        while self.peek_operator("or") {
+            self.next();
+            let rhs = self.parse_and()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr> {
+        let mut lhs = self.parse_primary()?;
+        loop {
+            match self.peek() {
+                None | Some(")") => break,
+                _ if self.peek_operator("or") => break,
+                _ if self.peek_operator("and") => {
+                    self.next();
+                }
+                _ => {}
+            }
+            let rhs = self.parse_primary()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr> {
+        let token = self
+            .next()
+            .ok_or_else(|| anyhow!("Filter ended unexpectedly"))?;
+        match token.as_str() {
+            "(" => {
+                let expr = self.parse_or()?;
+                match self.next().as_deref() {
+                    Some(")") => Ok(expr),
+                    _ => bail!("Missing closing parenthesis"),
+                }
+            }
+            ")" => bail!("Unexpected ')' in filter"),
+            op if op.eq_ignore_ascii_case("and") || op.eq_ignore_ascii_case("or") => {
+                bail!("Unexpected operator '{op}'")
+            }
+            term => self.parse_term(term),
+        }
+    }
+
+    fn parse_term(&self, term: &str) -> Result<Expr> {
+        if let Some(name) = term.strip_prefix('+') {
+            return parse_tag(name, true);
+        }
+        if let Some(name) = term.strip_prefix('-').filter(|name| !name.is_empty()) {
+            return parse_tag(name, false);
+        }
+        if let Some((name, value)) = term.split_once(':') {
+            let is_attr_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.');
+            if is_attr_name {
+                return self.parse_attr(name, value);
+            }
+        }
+        // checked before ids like taskwarrior does, a uuid can start with 8 digits
+        let is_uuid_prefix = term.len() >= 8
+            && term.chars().take(8).all(|c| c.is_ascii_hexdigit())
+            && term.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+        if is_uuid_prefix {
+            return Ok(Expr::UuidPrefix(term.to_lowercase()));
+        }
+        if let Some(ranges) = parse_ids(term) {
+            return Ok(Expr::Ids(ranges));
+        }
+        Ok(Expr::Word(term.to_lowercase()))
+    }
+
+    fn parse_attr(&self, name: &str, value: &str) -> Result<Expr> {
+        let (name, modifier) = match name.split_once('.') {
+            Some((name, modifier)) => (name, Modifier::from_name(modifier)?),
+            None => (name, Modifier::Default),
+        };
+        let attr = Attr::from_name(name)?;
+
+        let value = match (&attr, value) {
+            (_, "") => Value::Empty,
+            (Attr::Date(prop), value) => parse_date(value, self.now.naive_local())
+                .map(|date| {
+                    let tz = self.now.timezone();
+                    Value::Date(from_wall_clock(date, tz).with_timezone(&tz))
+                })
+                .ok_or_else(|| anyhow!("Could not parse {prop} date '{value}'"))?,
+            (Attr::Id, value) => value
+                .parse()
+                .map(Value::Number)
+                .map_err(|_| anyhow!("Invalid id '{value}'"))?,
+            (_, value) => Value::Text(value.to_lowercase()),
+        };
+
+        let supports_ordering = matches!(attr, Attr::Date(_) | Attr::Id);
+        if modifier.is_ordering() && !supports_ordering {
+            bail!("Modifier can only be used with dates or ids");
+        }
+        if supports_ordering
+            && matches!(
+                modifier,
+                Modifier::Has
+                    | Modifier::Hasnt
+                    | Modifier::Startswith
+                    | Modifier::Endswith
+                    | Modifier::Word
+                    | Modifier::Noword
+            )
+        {
+            bail!("Modifier can only be used with text attributes");
+        }
+
+        Ok(Expr::Attr {
+            attr,
+            modifier,
+            value,
+        })
+    }
+}
+
+fn parse_tag(name: &str, include: bool) -> Result<Expr> {
+    if name.chars().all(|c| c.is_ascii_uppercase()) {
+        let tag = VirtualTag::from_name(name)?;
+        return Ok(Expr::Virtual { tag, include });
+    }
+    let tag = Tag::try_from(name)?;
+    Ok(Expr::Tag { tag, include })
+}
+
+/// `3`, `1,4` or `2-5`
+fn parse_ids(term: &str) -> Option<Vec<(usize, usize)>> {
+    term.split(',')
+        .map(|part| match part.split_once('-') {
+            Some((from, to)) => Some((from.parse().ok()?, to.parse().ok()?)),
+            None => part.parse().ok().map(|id| (id, id)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::testing::task;
+    use chrono::{TimeZone, Utc};
+    use taskchampion::Operations;
+
+    /// Tuesday 2024-03-05 12:34:56, same reference as the datetime tests
+    fn now() -> DateTime<Tz> {
+        Tz::UTC.with_ymd_and_hms(2024, 3, 5, 12, 34, 56).unwrap()
+    }
+
+    fn at(days: i64) -> DateTime<Utc> {
+        (now() + Duration::days(days)).to_utc()
+    }
+
+    fn matches(filter: &str, task: &Task) -> bool {
+        Filter::parse(filter, now()).unwrap().matches(1, task)
+    }
+
+    fn tag(task: &mut Task, ops: &mut Operations, name: &str) {
+        task.add_tag(&Tag::try_from(name).unwrap(), ops).unwrap();
+    }
+
+    #[tokio::test]
+    async fn empty_filter_matches_everything() {
+        let t = task("anything", |_, _| {}).await;
+        let filter = Filter::parse("   ", now()).unwrap();
+        assert!(filter.is_empty());
+        assert!(filter.matches(1, &t));
+    }
+
+    #[tokio::test]
+    async fn tags_include_and_exclude() {
+        let t = task("tagged", |t, ops| tag(t, ops, "next")).await;
+        assert!(matches("+next", &t));
+        assert!(!matches("-next", &t));
+        assert!(!matches("+fi", &t));
+        assert!(matches("-fi", &t));
+    }
+
+    #[tokio::test]
+    async fn project_matches_hierarchy() {
+        let t = task("proj", |t, ops| {
+            t.set_user_defined_attribute("project", "work.home", ops)
+                .unwrap()
+        })
+        .await;
+        assert!(matches("project:work", &t));
+        assert!(matches("pro:work.home", &t));
+        assert!(!matches("project:wor", &t));
+        assert!(!matches("project.is:work", &t));
+        assert!(matches("project.has:hom", &t));
+        assert!(!matches("project:", &t));
+        assert!(matches("project.any:", &t));
+    }
+
+    #[tokio::test]
+    async fn date_modifiers() {
+        let t = task("due", |t, ops| t.set_due(Some(at(2)), ops).unwrap()).await;
+        assert!(matches("due.before:eow", &t));
+        assert!(matches("due.after:tomorrow", &t));
+        assert!(!matches("due.before:tomorrow", &t));
+        assert!(matches("due:2024-03-07", &t));
+        assert!(matches("due.by:2024-03-07T12:34:56", &t));
+        assert!(!matches("due:", &t));
+        assert!(matches("wait:", &t));
+    }
+
+    #[tokio::test]
+    async fn dates_are_read_on_the_users_clock() {
+        // 12:34 in UTC is 21:34 the same day in Tokyo
+        let now = now().with_timezone(&Tz::Asia__Tokyo);
+        let matches = |filter, task: &Task| Filter::parse(filter, now).unwrap().matches(1, task);
+
+        // 01:00 on the 6th in Tokyo, still the 5th in UTC
+        let due = Utc.with_ymd_and_hms(2024, 3, 5, 16, 0, 0).unwrap();
+        let t = task("late", |t, ops| t.set_due(Some(due), ops).unwrap()).await;
+        assert!(matches("+TOMORROW", &t));
+        assert!(!matches("+TODAY", &t));
+        assert!(matches("due:tomorrow", &t));
+        assert!(matches("due:2024-03-06", &t));
+        assert!(!matches("due:2024-03-05", &t));
+        assert!(matches("due.after:2024-03-06T00:30:00", &t));
+        assert!(!matches("due.after:2024-03-06T01:30:00", &t));
+    }
+
+    #[tokio::test]
+    async fn virtual_tags() {
+        let overdue = task("late", |t, ops| t.set_due(Some(at(-1)), ops).unwrap()).await;
+        assert!(matches("+OVERDUE", &overdue));
+        assert!(matches("+YESTERDAY +DUE", &overdue));
+        assert!(matches("+PENDING -WAITING", &overdue));
+        assert!(!matches("+TAGGED", &overdue));
+
+        // is_waiting compares against the real clock
+        let wait = Utc::now() + Duration::days(3);
+        let waiting = task("later", |t, ops| t.set_wait(Some(wait), ops).unwrap()).await;
+        assert!(matches("+WAITING", &waiting));
+        assert!(matches("status:waiting", &waiting));
+        assert!(!matches("-WAITING", &waiting));
+    }
+
+    #[tokio::test]
+    async fn and_or_parentheses() {
+        let t = task("write report", |t, ops| {
+            tag(t, ops, "work");
+            t.set_priority("h".into(), ops).unwrap();
+        })
+        .await;
+        assert!(matches("+home or +work", &t));
+        assert!(matches("+work and priority:H", &t));
+        assert!(!matches("+home or +work priority:l", &t));
+        assert!(matches("(+home or +work) priority:h", &t));
+        assert!(!matches("(+home or +work) and -work", &t));
+    }
+
+    #[tokio::test]
+    async fn bare_words_ids_and_uuids() {
+        let t = task("Write Report", |_, _| {}).await;
+        let uuid = t.get_uuid().to_string();
+        assert!(matches("report", &t));
+        assert!(!matches("invoice", &t));
+        assert!(matches("1", &t));
+        assert!(matches("3,1-2", &t));
+        assert!(!matches("2-4", &t));
+        assert!(matches(&uuid[..8], &t));
+        assert!(matches("description.startswith:write", &t));
+    }
+
+    #[tokio::test]
+    async fn uda_fallback() {
+        let t = task("uda", |t, ops| {
+            t.set_user_defined_attribute("estimate", "3h", ops)
+                .unwrap()
+        })
+        .await;
+        assert!(matches("estimate:3h", &t));
+        assert!(!matches("estimate:1h", &t));
+        assert!(matches("client:", &t));
+    }
+
+    #[test]
+    fn needs_all_tasks() {
+        let parse = |input| Filter::parse(input, now()).unwrap();
+        assert!(!parse("+next project:work").needs_all_tasks());
+        assert!(parse("status:completed").needs_all_tasks());
+        assert!(parse("+home or +WAITING").needs_all_tasks());
+        assert!(!parse("-ACTIVE").needs_all_tasks());
+        assert!(!parse("-WAITING").needs_all_tasks());
+        assert!(!parse("-COMPLETED").needs_all_tasks());
+        assert!(!parse("+PENDING").needs_all_tasks());
+        assert!(!parse("status.not:completed").needs_all_tasks());
+        assert!(parse("status.not:pending").needs_all_tasks());
+        assert!(!parse("status:pending").needs_all_tasks());
+        assert!(parse("end.after:yesterday").needs_all_tasks());
+        assert!(!parse("end:").needs_all_tasks());
+        assert!(parse("wait.any:").needs_all_tasks());
+
+        assert!(!parse("status:completed").includes_deleted());
+        assert!(parse("+work or status:deleted").includes_deleted());
+        assert!(parse("+DELETED").includes_deleted());
+        assert!(!parse("-DELETED").includes_deleted());
+        assert!(!parse("status.isnt:deleted").includes_deleted());
+    }
+
+    #[test]
+    fn eight_hex_digits_are_a_uuid_prefix() {
+        let parse = |input| Filter::parse(input, now()).unwrap().expr;
+        // taskwarrior reads 8 digits as a uuid prefix before an id
+        assert_eq!(parse("12345678"), Some(Expr::UuidPrefix("12345678".into())));
+        assert_eq!(parse("DEADBEEF"), Some(Expr::UuidPrefix("deadbeef".into())));
+        assert_eq!(
+            parse("deadbeef-12"),
+            Some(Expr::UuidPrefix("deadbeef-12".into()))
+        );
+        assert_eq!(parse("1234567"), Some(Expr::Ids(vec![(1234567, 1234567)])));
+        assert_eq!(parse("deadbee"), Some(Expr::Word("deadbee".into())));
+        assert_eq!(parse("1234-5678"), Some(Expr::Ids(vec![(1234, 5678)])));
+    }
+
+    #[test]
+    fn parse_errors() {
+        let parse = |input| Filter::parse(input, now());
+        assert!(parse("(+work").is_err());
+        assert!(parse("+work)").is_err());
+        assert!(parse("or +work").is_err());
+        assert!(parse("due.before:whenever").is_err());
+        assert!(parse("project.before:x").is_err());
+        assert!(parse("due.sortof:today").is_err());
+        assert!(parse("+NOTATAG").is_err());
+        assert!(parse("description:\"open").is_err());
+        assert!(parse("s:pending").is_err());
+    }
+}
@@ -0,0 +1,192 @@
+use chrono::DateTime;
+use chrono_tz::Tz;
+use taskchampion::Operation;
+use uuid::Uuid;
+
+/// Changes to a task made at the same moment, like a line of `task info`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// creations and deletions carry no timestamp of their own
+    pub at: Option<DateTime<Tz>>,
+    pub changes: Vec<String>,
+}
+
+/// a task's operation log as entries, newest first, times and dates in `tz`
+pub fn history(operations: &[Operation], tz: Tz) -> Vec<HistoryEntry> {
+    let mut entries: Vec<HistoryEntry> = vec![];
+    for operation in operations {
+        let at = match operation {
+            Operation::Update { timestamp, .. } => Some(timestamp.with_timezone(&tz)),
+            _ => None,
+        };
+        let Some((_, change)) = describe(operation, tz) else {
+            continue;
+        };
+        // every property of one edit gets its own timestamp, a moment apart
+        let same_moment = |last: &HistoryEntry| match (last.at, at) {
+            (Some(last), Some(at)) => (at - last).num_seconds().abs() < 2,
+            // a creation is followed by the updates filling in the task
+            (None, _) => true,
+            (Some(_), None) => false,
+        };
+        match entries.last_mut() {
+            Some(last) if same_moment(last) => {
+                last.at = last.at.or(at);
+                last.changes.push(change);
+            }
+            _ => entries.push(HistoryEntry {
+                at,
+                changes: vec![change],
+            }),
+        }
+    }
+    entries.reverse();
+    entries
+}
+
+/// properties holding epoch seconds
+const TIMESTAMPS: &[&str] = &["due", "wait", "scheduled", "until", "entry", "start", "end"];
+
+/// a change for people, `None` for bookkeeping nobody needs to see
+pub fn describe(operation: &Operation, tz: Tz) -> Option<(Uuid, String)> {
+    let change = match operation {
+        Operation::Create { uuid } => (*uuid, "created".to_owned()),
+        Operation::Delete { uuid, .. } => (*uuid, "removed for good".to_owned()),
+        Operation::Update {
+            uuid,
+            property,
+            old_value,
+            value,
+            ..
+        } => (*uuid, describe_update(property, old_value.as_deref(), value.as_deref(), tz)?),
+        Operation::UndoPoint => return None,
+    };
+    Some(change)
+}
+
+fn describe_update(
+    property: &str,
+    old: Option<&str>,
+    new: Option<&str>,
+    tz: Tz,
+) -> Option<String> {
+    if property == "modified" {
+        return None;
+    }
+    if let Some(tag) = property.strip_prefix("tag_") {
+        let verb = if new.is_some() { "added" } else { "removed" };
+        return Some(format!("{verb} tag +{tag}"));
+    }
+    if property.starts_with("dep_") {
+        let verb = if new.is_some() { "added" } else { "removed" };
+        return Some(format!("{verb} a dependency"));
+    }
+    if property.starts_with("annotation_") {
+        return Some(match new {
+            Some(text) => format!("annotated \"{text}\""),
+            None => "removed an annotation".to_owned(),
+        });
+    }
+
+    let show = |value: &str| {
+        if !TIMESTAMPS.contains(&property) {
+            return value.to_owned();
+        }
+        value
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| DateTime::from_timestamp(secs, 0))
+            .map(|date| date.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_else(|| value.to_owned())
+    };
+    Some(match (old, new) {
+        (None, Some(new)) => format!("set {property} to {}", show(new)),
+        (Some(old), None) => format!("removed {property} {}", show(old)),
+        (Some(old), Some(new)) => format!("changed {property} from {} to {}", show(old), show(new)),
+        (None, None) => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn update(property: &str, old: Option<&str>, new: Option<&str>) -> Operation {
+        Operation::Update {
+            uuid: Uuid::nil(),
+            property: property.to_owned(),
+            old_value: old.map(str::to_owned),
+            value: new.map(str::to_owned),
+            timestamp: Utc::now(),
+        }
+    }
+
+    fn text(operation: Operation) -> Option<String> {
+        describe(&operation, Tz::UTC).map(|(_, text)| text)
+    }
+
+    #[test]
+    fn describes_changes() {
+        assert_eq!(
+            text(Operation::Create { uuid: Uuid::nil() }).as_deref(),
+            Some("created")
+        );
+        assert_eq!(
+            text(update("status", Some("pending"), Some("completed"))).as_deref(),
+            Some("changed status from pending to completed")
+        );
+        assert_eq!(
+            text(update("due", None, Some("1767225600"))).as_deref(),
+            Some("set due to 2026-01-01 00:00")
+        );
+        assert_eq!(
+            describe(&update("due", None, Some("1767225600")), Tz::Asia__Tokyo)
+                .map(|(_, text)| text)
+                .as_deref(),
+            Some("set due to 2026-01-01 09:00")
+        );
+        assert_eq!(
+            text(update("tag_home", None, Some(""))).as_deref(),
+            Some("added tag +home")
+        );
+        assert_eq!(
+            text(update("annotation_1767225600", None, Some("call back"))).as_deref(),
+            Some("annotated \"call back\"")
+        );
+        assert_eq!(text(update("modified", None, Some("1767225600"))), None);
+        assert_eq!(text(Operation::UndoPoint), None);
+    }
+
+    #[test]
+    fn groups_changes_by_moment() {
+        let at = |secs: i64, property: &str, new: &str| Operation::Update {
+            uuid: Uuid::nil(),
+            property: property.to_owned(),
+            old_value: None,
+            value: Some(new.to_owned()),
+            timestamp: DateTime::from_timestamp(secs, 0).unwrap(),
+        };
+        let entries = history(
+            &[
+                Operation::Create { uuid: Uuid::nil() },
+                at(100, "description", "water plants"),
+                at(101, "modified", "101"),
+                at(101, "status", "pending"),
+                at(500, "project", "home"),
+            ],
+            Tz::UTC,
+        );
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].changes, vec!["set project to home"]);
+        assert_eq!(
+            entries[1].at,
+            DateTime::from_timestamp(100, 0).map(|at| at.with_timezone(&Tz::UTC))
+        );
+        assert_eq!(
+            entries[1].changes,
+            vec!["created", "set description to water plants", "set status to pending"]
+        );
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use taskchampion::Status;
+
+use super::task::TaskDto;
+
+/// iCalendar lines may be at most 75 octets before they have to be folded
+const LINE_LIMIT: usize = 75;
+
+/// An iCalendar document with a `VTODO` for every task, plus a `VEVENT` on
+/// the due date of due tasks for calendar apps that don't show todos
+pub fn feed(tasks: &[TaskDto], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//taskbane//taskbane//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+        "X-WR-CALNAME:Taskbane".to_owned(),
+    ];
+    for task in tasks {
+        lines.extend(todo(task, now));
+        if let Some(due) = task.due_at {
+            lines.extend(event(task, due, now));
+        }
+    }
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+fn todo(task: &TaskDto, now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VTODO".to_owned(),
+        format!("UID:{}@taskbane", task.uuid),
+        format!("DTSTAMP:{}", timestamp(now)),
+    ];
+    lines.extend(common(task));
+    if let Some(scheduled) = task.scheduled_at {
+        lines.push(format!("DTSTART:{}", timestamp(scheduled)));
+    }
+    if let Some(due) = task.due_at {
+        lines.push(format!("DUE:{}", timestamp(due)));
+    }
+    let status = match task.status {
+        Status::Completed => "COMPLETED",
+        Status::Deleted => "CANCELLED",
+        _ if task.is_active => "IN-PROCESS",
+        _ => "NEEDS-ACTION",
+    };
+    lines.push(format!("STATUS:{status}"));
+    if let Some(end) = task.end_at.filter(|_| task.status == Status::Completed) {
+        lines.push(format!("COMPLETED:{}", timestamp(end)));
+    }
+    lines.push("END:VTODO".to_owned());
+    lines
+}
+
+fn event(task: &TaskDto, due: DateTime<Utc>, now: DateTime<Utc>) -> Vec<String> {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_owned(),
+        format!("UID:{}-due@taskbane", task.uuid),
+        format!("DTSTAMP:{}", timestamp(now)),
+        format!("DTSTART:{}", timestamp(due)),
+        "DURATION:PT0S".to_owned(),
+        "TRANSP:TRANSPARENT".to_owned(),
+    ];
+    lines.extend(common(task));
+    let status = match task.status {
+        Status::Deleted => "CANCELLED",
+        _ => "CONFIRMED",
+    };
+    lines.push(format!("STATUS:{status}"));
+    lines.push("END:VEVENT".to_owned());
+    lines
+}
+
+/// properties todos and events share
+fn common(task: &TaskDto) -> Vec<String> {
+    let mut lines = vec![format!("SUMMARY:{}", escape(&task.description))];
+    // iCalendar priorities run from 1 (highest) to 9, 0 is undefined
+    let priority = match task.priority.to_lowercase().as_str() {
+        "h" => 1,
+        "m" => 5,
+        "l" => 9,
+        _ => 0,
+    };
+    if priority != 0 {
+        lines.push(format!("PRIORITY:{priority}"));
+    }
+    let mut categories: Vec<&str> = task.tags.split_whitespace().collect();
+    if !task.project.is_empty() {
+        categories.insert(0, &task.project);
+    }
+    if !categories.is_empty() {
+        let categories: Vec<String> = categories.into_iter().map(escape).collect();
+        lines.push(format!("CATEGORIES:{}", categories.join(",")));
+    }
+    if !task.annotations.is_empty() {
+        let notes: Vec<String> = task
+            .annotations
+            .iter()
+            .map(|annotation| {
+                format!(
+                    "{} {}",
+                    annotation.entry.format("%Y-%m-%d"),
+                    annotation.description
+                )
+            })
+            .collect();
+        lines.push(format!("DESCRIPTION:{}", escape(&notes.join("\n"))));
+    }
+    lines
+}
+
+fn timestamp(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// TEXT values escape backslashes, separators and newlines
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// a content line ending in CRLF, long lines continue on lines starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut width = 0;
+    for c in line.chars() {
+        // never split a character, continuation lines lose one octet to the space
+        if width + c.len_utf8() > LINE_LIMIT {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::testing::task_dto;
+    use chrono::NaiveDate;
+    use taskchampion::Tag;
+
+    #[test]
+    fn escapes_and_folds() {
+        assert_eq!(escape("a, b; c\\d\ne"), "a\\, b\\; c\\\\d\\ne");
+
+        let line = format!("SUMMARY:{}", "é".repeat(60));
+        let folded = fold(&line);
+        assert!(folded.ends_with("\r\n"));
+        assert!(folded
+            .trim_end()
+            .split("\r\n")
+            .all(|part| part.len() <= LINE_LIMIT));
+        assert_eq!(folded.replace("\r\n ", "").trim_end(), line);
+    }
+
+    #[tokio::test]
+    async fn maps_task_attributes() {
+        let due = NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(9, 30, 0)
+            .unwrap()
+            .and_utc();
+        let task = task_dto("Pay rent, today", |t, ops| {
+            t.set_due(Some(due), ops).unwrap();
+            t.set_priority("H".to_owned(), ops).unwrap();
+            t.add_tag(&Tag::try_from("bills").unwrap(), ops).unwrap();
+            t.set_user_defined_attribute("project", "home", ops)
+                .unwrap();
+        })
+        .await;
+
+        let ics = feed(&[task], due);
+        let lines: Vec<&str> = ics.split("\r\n").collect();
+        assert_eq!(lines.first(), Some(&"BEGIN:VCALENDAR"));
+        for expected in [
+            "BEGIN:VTODO",
+            "SUMMARY:Pay rent\\, today",
+            "DUE:20240305T093000Z",
+            "PRIORITY:1",
+            "CATEGORIES:home,bills",
+            "STATUS:NEEDS-ACTION",
+            "BEGIN:VEVENT",
+            "DTSTART:20240305T093000Z",
+        ] {
+            assert!(lines.contains(&expected), "missing {expected} in {ics}");
+        }
+    }
+}
@@ -1,3 +1,20 @@
+pub mod account;
+pub mod board;
+pub mod calendar;
+pub mod dependency;
+pub mod filter;
+pub mod history;
+pub mod ical;
+pub mod project;
+pub mod recurrence;
+pub mod report;
+pub mod sync;
 pub mod task;
+pub mod taskrc;
+#[cfg(test)]
+pub mod testing;
+pub mod uda;
+pub mod undo;
+pub mod urgency;
 pub mod user;
 pub mod user_auth;
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use taskchampion::Status;
+
+/// A project in the dotted hierarchy, counts include its subprojects
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectSummary {
+    /// full dotted name, `home.garden`
+    pub name: String,
+    pub depth: usize,
+    pub pending: usize,
+    pub completed: usize,
+}
+
+impl ProjectSummary {
+    /// every project with its parents, parents first and children in name order
+    pub fn tree<'a>(tasks: impl IntoIterator<Item = (&'a str, Status)>) -> Vec<ProjectSummary> {
+        // keyed by segments so `home.garden` sorts right after `home`, not after `home-office`
+        let mut counts: BTreeMap<Vec<&str>, (usize, usize)> = BTreeMap::new();
+
+        for (project, status) in tasks {
+            let segments: Vec<&str> = project.split('.').filter(|s| !s.is_empty()).collect();
+            for len in 1..=segments.len() {
+                let count = counts.entry(segments[..len].to_vec()).or_default();
+                match status {
+                    Status::Pending => count.0 += 1,
+                    Status::Completed => count.1 += 1,
+                    _ => {}
+                }
+            }
+        }
+
+        counts
+            .into_iter()
+            .map(|(segments, (pending, completed))| ProjectSummary {
+                name: segments.join("."),
+                depth: segments.len() - 1,
+                pending,
+                completed,
+            })
+            .collect()
+    }
+
+    /// the last segment, `garden` for `home.garden`
+    pub fn leaf(&self) -> &str {
+        self.name.rsplit('.').next().unwrap_or(&self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_hierarchy_with_rolled_up_counts() {
+        let tree = ProjectSummary::tree([
+            ("home.garden", Status::Pending),
+            ("home.garden", Status::Completed),
+            ("home", Status::Pending),
+            ("home-office", Status::Pending),
+            ("work.taskbane.ui", Status::Pending),
+            ("work", Status::Deleted),
+        ]);
+
+        let names: Vec<_> = tree
+            .iter()
+            .map(|project| (project.name.as_str(), project.depth))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("home", 0),
+                ("home.garden", 1),
+                ("home-office", 0),
+                ("work", 0),
+                ("work.taskbane", 1),
+                ("work.taskbane.ui", 2),
+            ]
+        );
+
+        assert_eq!((tree[0].pending, tree[0].completed), (2, 1));
+        assert_eq!((tree[1].pending, tree[1].completed), (1, 1));
+        assert_eq!((tree[3].pending, tree[3].completed), (1, 0));
+        assert_eq!(tree[5].leaf(), "ui");
+    }
+}
@@ -0,0 +1,235 @@
+use std::{iter, str::FromStr};
+
+use anyhow::{anyhow, bail, Error, Result};
+use chrono::{DateTime, Datelike, Duration, Months, Utc, Weekday};
+use taskchampion::Task;
+
+/// How often a recurring task repeats, parsed from taskwarrior `recur` values
+/// like `weekly`, `3d`, `2mo` or `P1W`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Period {
+    Days(u32),
+    Months(u32),
+    /// every day from monday to friday
+    Weekdays,
+}
+
+impl FromStr for Period {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let recur = s.trim().to_lowercase();
+        let named = match recur.as_str() {
+            "daily" | "day" => Some(Period::Days(1)),
+            "weekdays" => Some(Period::Weekdays),
+            "weekly" | "week" => Some(Period::Days(7)),
+            "biweekly" | "fortnight" => Some(Period::Days(14)),
+            "monthly" | "month" => Some(Period::Months(1)),
+            "bimonthly" => Some(Period::Months(2)),
+            "quarterly" | "quarter" => Some(Period::Months(3)),
+            "semiannual" => Some(Period::Months(6)),
+            "annual" | "yearly" | "year" => Some(Period::Months(12)),
+            "biannual" | "biyearly" => Some(Period::Months(24)),
+            _ => None,
+        };
+        if let Some(period) = named {
+            return Ok(period);
+        }
+
+        // `P1W` as well as `1w`, `1wk`, `1 weeks`
+        let amount = recur.strip_prefix('p').unwrap_or(&recur);
+        let split = amount
+            .find(|c: char| !c.is_ascii_digit())
+            .ok_or_else(|| anyhow!("Missing unit in recurrence '{s}'"))?;
+        let (count, unit) = amount.split_at(split);
+        let count: u32 = match count {
+            "" => 1,
+            count => count.parse()?,
+        };
+        if count == 0 {
+            bail!("Recurrence '{s}' never repeats");
+        }
+
+        let period = match unit.trim() {
+            "d" | "day" | "days" => Period::Days(count),
+            "w" | "wk" | "wks" | "week" | "weeks" => Period::Days(count * 7),
+            "m" | "mo" | "mth" | "mths" | "month" | "months" => Period::Months(count),
+            "q" | "qtr" | "qtrs" | "quarter" | "quarters" => Period::Months(count * 3),
+            "y" | "yr" | "yrs" | "year" | "years" => Period::Months(count * 12),
+            _ => bail!("Unknown recurrence '{s}'"),
+        };
+        Ok(period)
+    }
+}
+
+impl Period {
+    /// every due date from `start` on, `start` included
+    fn dates(self, start: DateTime<Utc>) -> Box<dyn Iterator<Item = DateTime<Utc>>> {
+        match self {
+            Period::Days(days) => {
+                Box::new((0..).map(move |n: i64| start + Duration::days(n * days as i64)))
+            }
+            // from the start each time, so the 31st doesn't drift to the 28th after february
+            Period::Months(months) => Box::new(
+                (0..).map_while(move |n: u32| start.checked_add_months(Months::new(n * months))),
+            ),
+            Period::Weekdays => Box::new(iter::successors(Some(start), |date| {
+                let mut next = *date + Duration::days(1);
+                while matches!(next.weekday(), Weekday::Sat | Weekday::Sun) {
+                    next += Duration::days(1);
+                }
+                Some(next)
+            })),
+        }
+    }
+}
+
+/// The template side of a recurring task, what its children are built from
+#[derive(Debug, Clone)]
+pub struct Recurrence {
+    pub period: Period,
+    pub due: DateTime<Utc>,
+    pub until: Option<DateTime<Utc>>,
+    pub wait: Option<DateTime<Utc>>,
+    pub scheduled: Option<DateTime<Utc>>,
+}
+
+/// a child task to create, `imask` is its index in the template's mask
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceInstance {
+    pub imask: usize,
+    pub due: DateTime<Utc>,
+    pub wait: Option<DateTime<Utc>>,
+    pub scheduled: Option<DateTime<Utc>>,
+}
+
+impl Recurrence {
+    /// the recurrence of a periodic template, `None` when it has no `recur` or `due`.
+    /// Chained recurrence only continues from the desktop, on completion, so it's `None` too
+    pub fn of_template(template: &Task) -> Result<Option<Self>> {
+        if template.get_value("rtype") == Some("chained") {
+            return Ok(None);
+        }
+        let (Some(recur), Some(due)) = (template.get_value("recur"), template.get_due()) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            period: recur.parse()?,
+            due,
+            until: template.get_timestamp("until"),
+            wait: template.get_wait(),
+            scheduled: template.get_timestamp("scheduled"),
+        }))
+    }
+
+    /// instances not generated yet: every one due by `now` and the next upcoming one,
+    /// like taskwarrior with `recurrence.limit=1`. `generated` is the template mask length.
+    pub fn pending_instances(
+        &self,
+        generated: usize,
+        now: DateTime<Utc>,
+    ) -> Vec<RecurrenceInstance> {
+        let mut instances = vec![];
+        for (imask, due) in self.period.dates(self.due).enumerate() {
+            if self.until.is_some_and(|until| due > until) {
+                break;
+            }
+            if imask >= generated {
+                // wait and scheduled keep their distance to due
+                instances.push(RecurrenceInstance {
+                    imask,
+                    due,
+                    wait: self.wait.map(|wait| due + (wait - self.due)),
+                    scheduled: self.scheduled.map(|scheduled| due + (scheduled - self.due)),
+                });
+            }
+            if due > now {
+                break;
+            }
+        }
+        instances
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn date(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn parses_taskwarrior_periods() {
+        assert_eq!("weekly".parse::<Period>().unwrap(), Period::Days(7));
+        assert_eq!("3d".parse::<Period>().unwrap(), Period::Days(3));
+        assert_eq!("2 weeks".parse::<Period>().unwrap(), Period::Days(14));
+        assert_eq!("P1M".parse::<Period>().unwrap(), Period::Months(1));
+        assert_eq!("2y".parse::<Period>().unwrap(), Period::Months(24));
+        assert_eq!("Weekdays".parse::<Period>().unwrap(), Period::Weekdays);
+        assert!("0d".parse::<Period>().is_err());
+        assert!("3h".parse::<Period>().is_err());
+        assert!("often".parse::<Period>().is_err());
+    }
+
+    #[test]
+    fn generates_missed_and_next_instances() {
+        let recurrence = Recurrence {
+            period: Period::Days(7),
+            due: date(2026, 1, 1),
+            until: None,
+            wait: Some(date(2025, 12, 30)),
+            scheduled: None,
+        };
+        let now = date(2026, 1, 10);
+
+        let instances = recurrence.pending_instances(0, now);
+        let dues: Vec<_> = instances.iter().map(|i| (i.imask, i.due)).collect();
+        assert_eq!(
+            dues,
+            vec![(0, date(2026, 1, 1)), (1, date(2026, 1, 8)), (2, date(2026, 1, 15))]
+        );
+        assert_eq!(instances[2].wait, Some(date(2026, 1, 13)));
+
+        // already generated ones are skipped, nothing new until the next one is due
+        assert!(recurrence.pending_instances(3, now).is_empty());
+        assert_eq!(recurrence.pending_instances(3, date(2026, 1, 16)).len(), 1);
+    }
+
+    #[test]
+    fn months_and_until() {
+        let recurrence = Recurrence {
+            period: Period::Months(1),
+            due: date(2026, 1, 31),
+            until: Some(date(2026, 3, 1)),
+            wait: None,
+            scheduled: None,
+        };
+        let dues: Vec<_> = recurrence
+            .pending_instances(0, date(2026, 6, 1))
+            .into_iter()
+            .map(|i| i.due)
+            .collect();
+        assert_eq!(dues, vec![date(2026, 1, 31), date(2026, 2, 28)]);
+    }
+
+    #[test]
+    fn weekdays_skip_weekends() {
+        let recurrence = Recurrence {
+            period: Period::Weekdays,
+            // a friday
+            due: date(2026, 1, 2),
+            until: None,
+            wait: None,
+            scheduled: None,
+        };
+        let dues: Vec<_> = recurrence
+            .pending_instances(0, date(2026, 1, 4))
+            .into_iter()
+            .map(|i| i.due)
+            .collect();
+        assert_eq!(dues, vec![date(2026, 1, 2), date(2026, 1, 5)]);
+    }
+}
@@ -0,0 +1,367 @@
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Error, Result};
+use derive_more::Constructor;
+use itertools::Itertools;
+
+use super::task::TaskDto;
+
+/// columns a report can show on a task card, in display order
+pub const COLUMNS: &[&str] = &[
+    "id",
+    "start",
+    "end",
+    "due",
+    "scheduled",
+    "wait",
+    "urgency",
+    "priority",
+    "project",
+    "recur",
+    "description",
+    "tags",
+    "depends",
+];
+
+/// A named task list, the taskbane take on a taskwarrior report
+#[derive(Debug, Clone, Constructor)]
+pub struct Report {
+    pub name: String,
+    pub description: String,
+    pub filter: String,
+    pub sort: Sort,
+    pub columns: Columns,
+    /// shipped with taskbane and not overridden by the user
+    pub builtin: bool,
+}
+
+impl Report {
+    /// defaults mirroring taskwarrior's own reports
+    pub fn builtins() -> Vec<Report> {
+        let builtin = |name: &str, description: &str, filter: &str, sort: &str, columns: &str| {
+            Report::new(
+                name.to_owned(),
+                description.to_owned(),
+                filter.to_owned(),
+                sort.parse().unwrap_or_default(),
+                columns.parse().unwrap_or_default(),
+                true,
+            )
+        };
+        vec![
+            builtin(
+                "next",
+                "Most urgent tasks",
+                "status:pending -WAITING",
+                "urgency-",
+                "id,start,due,urgency,priority,project,description,tags,depends",
+            ),
+            builtin(
+                "ready",
+                "Actionable tasks",
+                "+READY",
+                "urgency-",
+                "id,start,due,urgency,priority,project,description,tags,depends",
+            ),
+            builtin(
+                "waiting",
+                "Waiting (hidden) tasks",
+                "+WAITING",
+                "wait+,due+",
+                "id,wait,due,priority,project,description,tags",
+            ),
+            builtin(
+                "overdue",
+                "Overdue tasks",
+                "+OVERDUE",
+                "urgency-,due+",
+                "id,start,due,urgency,priority,project,description,tags",
+            ),
+            builtin(
+                "completed",
+                "Completed tasks",
+                "status:completed",
+                "end-",
+                "end,priority,description,tags",
+            ),
+            builtin(
+                "recurring",
+                "Recurring tasks",
+                "(+PARENT or +CHILD) -COMPLETED -DELETED",
+                "due+,urgency-",
+                "id,due,recur,urgency,priority,project,description,tags",
+            ),
+        ]
+    }
+
+    /// the report's filter narrowed by `extra`, each part grouped so an `or` stays
+    /// inside it, empty parts are left out
+    pub fn filter_with(&self, extra: &str) -> String {
+        [self.filter.trim(), extra.trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .map(|part| format!("({part})"))
+            .join(" ")
+    }
+
+    /// report names end up in urls, keep them simple
+    pub fn validate_name(name: &str) -> Result<()> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+        if !valid {
+            bail!("Report names may only use a-z, 0-9, '-' and '_'");
+        }
+        // would be shadowed by the report routes
+        if name == "nav" {
+            bail!("Report name '{name}' is reserved");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SortField {
+    Id,
+    Urgency,
+    Due,
+    Scheduled,
+    Wait,
+    End,
+    Priority,
+    Project,
+    Description,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SortKey {
+    field: SortField,
+    descending: bool,
+}
+
+/// taskwarrior style sort keys, `urgency-,due+`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Sort(Vec<SortKey>);
+
+impl Sort {
+    /// stable, so tasks keep their urgency order when keys tie
+    pub fn apply(&self, tasks: &mut [TaskDto]) {
+        tasks.sort_by(|a, b| {
+            self.0
+                .iter()
+                .map(|key| key.compare(a, b))
+                .find(|ord| ord.is_ne())
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+}
+
+impl SortKey {
+    fn compare(&self, a: &TaskDto, b: &TaskDto) -> Ordering {
+        let dates = match self.field {
+            SortField::Id => return self.directed(a.id.cmp(&b.id)),
+            SortField::Urgency => return self.directed(a.urgency.total_cmp(&b.urgency)),
+            SortField::Description => {
+                return self.directed(a.description.cmp(&b.description));
+            }
+            SortField::Project => return self.directed(a.project.cmp(&b.project)),
+            SortField::Priority => {
+                return self.directed(priority_rank(&a.priority).cmp(&priority_rank(&b.priority)));
+            }
+            SortField::Due => (a.due_at, b.due_at),
+            SortField::Scheduled => (a.scheduled_at, b.scheduled_at),
+            SortField::Wait => (a.wait_at, b.wait_at),
+            SortField::End => (a.end_at, b.end_at),
+        };
+        // tasks without the date go last either way, like taskwarrior
+        match dates {
+            (Some(a), Some(b)) => self.directed(a.cmp(&b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+
+    fn directed(&self, ord: Ordering) -> Ordering {
+        if self.descending {
+            ord.reverse()
+        } else {
+            ord
+        }
+    }
+}
+
+fn priority_rank(priority: &str) -> u8 {
+    match priority.to_ascii_lowercase().as_str() {
+        "h" => 3,
+        "m" => 2,
+        "l" => 1,
+        _ => 0,
+    }
+}
+
+impl FromStr for Sort {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|key| !key.is_empty())
+            .map(|key| {
+                let (name, descending) = match key.strip_suffix('-') {
+                    Some(name) => (name, true),
+                    None => (key.strip_suffix('+').unwrap_or(key), false),
+                };
+                let field = match name {
+                    "id" => SortField::Id,
+                    "urgency" => SortField::Urgency,
+                    "due" => SortField::Due,
+                    "scheduled" => SortField::Scheduled,
+                    "wait" => SortField::Wait,
+                    "end" => SortField::End,
+                    "priority" => SortField::Priority,
+                    "project" => SortField::Project,
+                    "description" => SortField::Description,
+                    _ => return Err(anyhow!("Unknown sort key '{name}'")),
+                };
+                Ok(SortKey { field, descending })
+            })
+            .collect::<Result<_>>()
+            .map(Sort)
+    }
+}
+
+impl fmt::Display for Sort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut keys = self.0.iter().map(|key| {
+            let name = match key.field {
+                SortField::Id => "id",
+                SortField::Urgency => "urgency",
+                SortField::Due => "due",
+                SortField::Scheduled => "scheduled",
+                SortField::Wait => "wait",
+                SortField::End => "end",
+                SortField::Priority => "priority",
+                SortField::Project => "project",
+                SortField::Description => "description",
+            };
+            format!("{name}{}", if key.descending { '-' } else { '+' })
+        });
+        write!(f, "{}", keys.join(","))
+    }
+}
+
+/// the columns a report renders, `id,due,description`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns(Vec<&'static str>);
+
+impl Columns {
+    pub fn show(&self, name: &str) -> bool {
+        self.0.contains(&name)
+    }
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Columns(COLUMNS.iter().copied().filter(|col| *col != "end").collect())
+    }
+}
+
+impl FromStr for Columns {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let columns = s
+            .split(',')
+            .map(str::trim)
+            .filter(|col| !col.is_empty())
+            .map(|col| {
+                COLUMNS
+                    .iter()
+                    .find(|known| **known == col)
+                    .copied()
+                    .ok_or_else(|| anyhow!("Unknown column '{col}'"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if columns.is_empty() {
+            bail!("A report needs at least one column");
+        }
+        Ok(Columns(columns))
+    }
+}
+
+impl fmt::Display for Columns {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.iter().join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::filter::Filter;
+    use chrono::Utc;
+    use chrono_tz::Tz;
+
+    #[test]
+    fn extra_filters_narrow_the_report() {
+        let report = |filter: &str| {
+            Report::new(
+                "mine".to_owned(),
+                String::new(),
+                filter.to_owned(),
+                Default::default(),
+                Default::default(),
+                false,
+            )
+        };
+        assert_eq!(
+            report("+home or +work").filter_with("due:today"),
+            "(+home or +work) (due:today)"
+        );
+        assert_eq!(report("").filter_with(" +next "), "(+next)");
+        assert_eq!(report("+home").filter_with("  "), "(+home)");
+        assert_eq!(report("").filter_with(""), "");
+        let now = Utc::now().with_timezone(&Tz::UTC);
+        assert!(Filter::parse(&report("").filter_with("+next"), now).is_ok());
+    }
+
+    #[test]
+    fn builtins_parse() {
+        for report in Report::builtins() {
+            assert!(!report.sort.0.is_empty(), "{} has no sort", report.name);
+            assert!(report.columns.show("description") || report.columns.show("id"));
+            Filter::parse(&report.filter, Utc::now().with_timezone(&Tz::UTC)).unwrap();
+            Report::validate_name(&report.name).unwrap();
+        }
+    }
+
+    #[test]
+    fn sort_round_trips() {
+        let sort: Sort = "urgency-, due, id+".parse().unwrap();
+        assert_eq!(sort.to_string(), "urgency-,due+,id+");
+        let sort: Sort = "wait,scheduled-".parse().unwrap();
+        assert_eq!(sort.to_string(), "wait+,scheduled-");
+        assert!("entry+".parse::<Sort>().is_err());
+    }
+
+    #[test]
+    fn columns_are_validated() {
+        let columns: Columns = "id,description".parse().unwrap();
+        assert!(columns.show("id"));
+        assert!(!columns.show("due"));
+        assert_eq!(columns.to_string(), "id,description");
+        assert!("id,colour".parse::<Columns>().is_err());
+        assert!("".parse::<Columns>().is_err());
+        assert!(!Columns::default().show("end"));
+    }
+
+    #[test]
+    fn names_are_url_safe() {
+        assert!(Report::validate_name("work-next").is_ok());
+        assert!(Report::validate_name("Work").is_err());
+        assert!(Report::validate_name("a/b").is_err());
+        assert!(Report::validate_name("nav").is_err());
+    }
+}
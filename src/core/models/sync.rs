@@ -0,0 +1,94 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Where the replica stands with the sync server
+#[derive(Debug, Clone, Default)]
+pub struct SyncStatus {
+    pub last_attempt: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    /// why the last attempt failed, `None` when it didn't
+    pub last_error: Option<String>,
+    /// when a failed sync is tried again
+    pub retry_at: Option<DateTime<Utc>>,
+    pub syncing: bool,
+    /// no sync backend is configured
+    pub local_only: bool,
+    /// local operations the server hasn't seen yet
+    pub unsynced: usize,
+    /// the last server version synced, `None` before the first sync
+    pub base_version: Option<Uuid>,
+}
+
+/// The one word summary the navbar shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncState {
+    /// there is nothing to sync with
+    LocalOnly,
+    Syncing,
+    Failing,
+    /// changes are waiting for the next sync
+    Unsynced,
+    Synced,
+    /// nothing attempted yet since the server started
+    Never,
+}
+
+impl SyncStatus {
+    pub fn state(&self) -> SyncState {
+        if self.local_only {
+            SyncState::LocalOnly
+        } else if self.syncing {
+            SyncState::Syncing
+        } else if self.last_error.is_some() {
+            SyncState::Failing
+        } else if self.unsynced > 0 {
+            SyncState::Unsynced
+        } else if self.last_success.is_some() {
+            SyncState::Synced
+        } else {
+            SyncState::Never
+        }
+    }
+}
+
+impl fmt::Display for SyncState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SyncState::LocalOnly => "local",
+            SyncState::Syncing => "syncing",
+            SyncState::Failing => "failing",
+            SyncState::Unsynced => "unsynced",
+            SyncState::Synced => "synced",
+            SyncState::Never => "never",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_outrank_pending_changes() {
+        let mut status = SyncStatus::default();
+        assert_eq!(status.state(), SyncState::Never);
+
+        status.last_success = Some(Utc::now());
+        assert_eq!(status.state(), SyncState::Synced);
+
+        status.unsynced = 3;
+        assert_eq!(status.state(), SyncState::Unsynced);
+
+        status.last_error = Some("connection refused".to_owned());
+        assert_eq!(status.state(), SyncState::Failing);
+
+        status.syncing = true;
+        assert_eq!(status.state(), SyncState::Syncing);
+
+        status.local_only = true;
+        assert_eq!(status.state(), SyncState::LocalOnly);
+    }
+}
@@ -1,27 +1,12 @@
-use std::str::FromStr;
-
+use chrono_tz::Tz;
 use itertools::Itertools;
 use taskchampion::{
-    chrono::{DateTime, Duration, Local, Utc},
-    Annotation, Status, Tag, Task,
+    chrono::{DateTime, Duration, Utc},
+    Annotation, Status, Task,
 };
 use uuid::Uuid;
 
-// urgency coefficient
-const NEXT_TAG: f64 = 15.0;
-const DUE: f64 = 12.0;
-const BLOCKING_OTHERS: f64 = 8.0;
-const PRIORITY_HIGH: f64 = 6.0;
-const PRIORITY_MEDIUM: f64 = 3.9;
-const PRIORITY_LOW: f64 = -2.0;
-const TAG_FI: f64 = 4.0;
-const ACTIVE_STARTED: f64 = 4.0;
-const TASK_AGE: f64 = 2.0;
-const PROJECT_ASSIGNED: f64 = 1.0;
-const TAGS_COUNT: f64 = 1.0;
-const ANNOTATIONS_COUNT: f64 = 1.0;
-const WAITING: f64 = -3.0;
-const BLOCKED: f64 = -5.0;
+use super::{uda, urgency::UrgencyCoefficients};
 
 #[derive(Debug, Clone)]
 pub enum TaskDueStatus {
@@ -39,59 +24,110 @@ pub struct TaskDto {
     pub status: Status,
     pub description: String,
     pub due: Option<String>,
+    pub due_at: Option<DateTime<Utc>>,
     pub due_status: TaskDueStatus,
+    /// time left until a waiting task shows up again
+    pub wait: Option<String>,
+    pub wait_at: Option<DateTime<Utc>>,
+    pub scheduled: Option<String>,
+    pub scheduled_at: Option<DateTime<Utc>>,
     pub annotations: Vec<Annotation>,
 
     pub is_blocked: bool,
     pub is_blocking: bool,
+    pub is_active: bool,
+    pub started: Option<String>,
+    pub ended: Option<String>,
+    pub end_at: Option<DateTime<Utc>>,
 
     pub tags: String,
     pub deps: String,
+    pub dep_uuids: Vec<Uuid>,
 
     pub priority: String,
+    pub project: String,
+    /// taskwarrior `recur` value, set on templates and their children
+    pub recur: String,
+    pub until_at: Option<DateTime<Utc>>,
+    /// user defined attributes as stored, sorted by name
+    pub udas: Vec<(String, String)>,
     pub urgency: f64,
+    /// the owner's timezone, dates are shown and edited in it
+    pub tz: Tz,
 }
 
 impl TaskDto {
-    pub fn from(id: usize, task: Task, deps: Vec<usize>) -> Self {
-        let next_urg = Tag::from_str("next")
-            .map(|tag| if task.has_tag(&tag) { NEXT_TAG } else { 0. })
-            .unwrap_or(0.);
-        let fi_urg = Tag::from_str("fi")
-            .map(|tag| if task.has_tag(&tag) { TAG_FI } else { 0. })
-            .unwrap_or(0.);
-
-        let due_urg = task.get_due().map(Self::due_urgency).unwrap_or_default();
+    /// which list the task shows up in, `waiting` for pending tasks hidden until later
+    pub fn listed_as(&self) -> &'static str {
+        match self.status {
+            Status::Pending if self.wait.is_some() => "waiting",
+            Status::Pending => "pending",
+            Status::Completed => "completed",
+            Status::Deleted => "deleted",
+            Status::Recurring => "recurring",
+            Status::Unknown(_) => "unknown",
+        }
+    }
+
+    /// `date` on the owner's wall clock
+    pub fn local(&self, date: &DateTime<Utc>) -> DateTime<Tz> {
+        date.with_timezone(&self.tz)
+    }
+
+    pub fn from(
+        id: usize,
+        task: Task,
+        deps: Vec<usize>,
+        coeffs: &UrgencyCoefficients,
+        tz: Tz,
+    ) -> Self {
+        let due_urg = task
+            .get_due()
+            .map(|due| Self::due_urgency(due) * coeffs.due)
+            .unwrap_or_default();
         let due_status = task
             .get_due()
-            .map(Self::due_status)
+            .map(|due| Self::due_status(due, Utc::now().with_timezone(&tz)))
             .unwrap_or(TaskDueStatus::Not);
         let due = task.get_due().map(Self::due);
         let blocking_urg = if task.is_blocking() {
-            BLOCKING_OTHERS
+            coeffs.blocking
         } else {
             0.
         };
         let pri_urg = match task.get_priority() {
-            "h" => PRIORITY_HIGH,
-            "m" => PRIORITY_MEDIUM,
-            "l" => PRIORITY_LOW,
-            _ => 0.,
+            "" => 0.,
+            priority => coeffs.uda("priority", priority),
         };
-        let act_urg = if task.is_active() { ACTIVE_STARTED } else { 0. };
-
+        let uda_urg: f64 = task
+            .get_user_defined_attributes()
+            .map(|(name, value)| coeffs.uda(name, value))
+            .sum();
+        let act_urg = if task.is_active() { coeffs.active } else { 0. };
+        let started = task
+            .get_timestamp("start")
+            .filter(|_| task.is_active())
+            .map(Self::elapsed);
+        let end_at = task.get_timestamp("end");
         let age_urg = task
             .get_entry()
-            .map(|age| (Utc::now() - age).num_days().clamp(0, 365) as f64)
-            .map(|age_days| (age_days / 365.0) * TASK_AGE)
+            .filter(|_| coeffs.age_max > 0.)
+            .map(|age| ((Utc::now() - age).num_days() as f64).clamp(0., coeffs.age_max))
+            .map(|age_days| (age_days / coeffs.age_max) * coeffs.age)
             .unwrap_or_default();
 
         let proj_urg = task
             .get_user_defined_attribute("project")
-            .map(|_| PROJECT_ASSIGNED)
+            .map(|project| coeffs.project + coeffs.project(project))
             .unwrap_or_default();
-        let wait_urg = if task.is_waiting() { WAITING } else { 0. };
-        let block_urg = if task.is_blocked() { BLOCKED } else { 0. };
+        let wait_urg = if task.is_waiting() { coeffs.waiting } else { 0. };
+        let scheduled_at = task.get_timestamp("scheduled");
+        let sched_urg = if scheduled_at.is_some_and(|scheduled| scheduled < Utc::now()) {
+            coeffs.scheduled
+        } else {
+            0.
+        };
+        let block_urg = if task.is_blocked() { coeffs.blocked } else { 0. };
 
         let user_tags: Vec<_> = task.get_tags().filter(|tag| tag.is_user()).collect();
         let tags_urg = match user_tags.len() {
@@ -99,7 +135,8 @@ impl TaskDto {
             1 => 0.8,
             2 => 0.9,
             _ => 1.0,
-        } * TAGS_COUNT;
+        } * coeffs.tags;
+        let user_tag_urg: f64 = user_tags.iter().map(|tag| coeffs.tag(tag.as_ref())).sum();
 
         let annotations = task
             .get_annotations()
@@ -111,7 +148,7 @@ impl TaskDto {
             1 => 0.5,
             2 => 0.7,
             _ => 1.0,
-        } * ANNOTATIONS_COUNT;
+        } * coeffs.annotations;
 
         Self {
             id,
@@ -119,15 +156,36 @@ impl TaskDto {
             status: task.get_status(),
             description: task.get_description().to_owned(),
             priority: task.get_priority().to_owned(),
+            project: task
+                .get_user_defined_attribute("project")
+                .unwrap_or_default()
+                .to_owned(),
+            recur: task.get_value("recur").unwrap_or_default().to_owned(),
+            until_at: task.get_timestamp("until"),
+            udas: task
+                .get_user_defined_attributes()
+                .filter(|(name, _)| !uda::is_reserved(name))
+                .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                .sorted()
+                .collect(),
             is_blocked: task.is_blocked(),
             is_blocking: task.is_blocking(),
+            is_active: task.is_active(),
+            started,
+            ended: end_at.map(Self::elapsed),
+            end_at,
             tags: user_tags.iter().join(" "),
             deps: deps.iter().join(" "),
+            dep_uuids: task.get_dependencies().collect(),
             annotations,
             due,
+            due_at: task.get_due(),
             due_status,
-            urgency: next_urg
-                + fi_urg
+            wait: task.get_wait().filter(|_| task.is_waiting()).map(Self::due),
+            wait_at: task.get_wait(),
+            scheduled: scheduled_at.map(Self::due),
+            scheduled_at,
+            urgency: user_tag_urg
                 + due_urg
                 + blocking_urg
                 + pri_urg
@@ -137,7 +195,10 @@ impl TaskDto {
                 + tags_urg
                 + annote_urg
                 + wait_urg
-                + block_urg,
+                + sched_urg
+                + block_urg
+                + uda_urg,
+            tz,
         }
     }
 
@@ -145,14 +206,14 @@ impl TaskDto {
         // days_overdue: positive = overdue, negative = future
         let days_overdue = (Utc::now() - due).num_seconds() as f64 / 86_400.0;
         let term = ((days_overdue + 14.0) * 0.8 / 21.0) + 0.2;
-        term.clamp(0.2, 1.0) * DUE
+        term.clamp(0.2, 1.0)
     }
 
-    fn due_status(due: DateTime<Utc>) -> TaskDueStatus {
-        let now = Local::now();
+    /// due today is by the calendar day of `now`, the owner's
+    fn due_status(due: DateTime<Utc>, now: DateTime<Tz>) -> TaskDueStatus {
         if due < now {
             TaskDueStatus::OverDue
-        } else if due < now + Duration::hours(24) {
+        } else if due.with_timezone(&now.timezone()).date_naive() == now.date_naive() {
             TaskDueStatus::DueToday
         } else if due < now + Duration::days(7) {
             TaskDueStatus::DueSoon
@@ -162,7 +223,14 @@ impl TaskDto {
     }
 
     fn due(due: DateTime<Utc>) -> String {
-        let delta = due.signed_duration_since(Local::now());
+        Self::humanize(due.signed_duration_since(Utc::now()))
+    }
+
+    fn elapsed(start: DateTime<Utc>) -> String {
+        Self::humanize(Utc::now().signed_duration_since(start))
+    }
+
+    fn humanize(delta: Duration) -> String {
         let secs = delta.num_seconds().abs();
 
         match secs {
@@ -176,3 +244,30 @@ impl TaskDto {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn due_today_is_the_owners_day() {
+        let tz = Tz::America__New_York;
+        // 18:00 on the 5th in New York, due at 21:00 there, already the 6th in UTC
+        let now = Utc.with_ymd_and_hms(2024, 3, 5, 23, 0, 0).unwrap();
+        let due = Utc.with_ymd_and_hms(2024, 3, 6, 2, 0, 0).unwrap();
+
+        assert!(matches!(
+            TaskDto::due_status(due, now.with_timezone(&tz)),
+            TaskDueStatus::DueToday
+        ));
+        assert!(matches!(
+            TaskDto::due_status(due, now.with_timezone(&Tz::UTC)),
+            TaskDueStatus::DueSoon
+        ));
+        assert!(matches!(
+            TaskDto::due_status(now - Duration::hours(1), now.with_timezone(&tz)),
+            TaskDueStatus::OverDue
+        ));
+    }
+}
@@ -0,0 +1,298 @@
+use itertools::Itertools;
+
+use super::report::{Sort, COLUMNS};
+
+/// The parts of a taskwarrior `.taskrc` taskbane knows what to do with
+#[derive(Debug, Default, PartialEq)]
+pub struct Taskrc {
+    /// `urgency.*` keys, checked with `UrgencyCoefficients::check` when they are saved
+    pub urgency: Vec<(String, String)>,
+    pub reports: Vec<TaskrcReport>,
+    pub udas: Vec<TaskrcUda>,
+    /// `dateformat`, `weekstart` and `context.*`, kept verbatim
+    pub settings: Vec<(String, String)>,
+    /// files pulled in with `include`, an upload can't follow them
+    pub includes: Vec<String>,
+    /// lines taskbane has no use for, `line 12: color.due`
+    pub unknown: Vec<String>,
+}
+
+/// a `report.<name>.*` definition, attributes left out keep their defaults
+#[derive(Debug, Default, PartialEq)]
+pub struct TaskrcReport {
+    pub name: String,
+    pub description: Option<String>,
+    pub filter: Option<String>,
+    pub sort: Option<String>,
+    pub columns: Option<String>,
+}
+
+/// a `uda.<name>.*` definition
+#[derive(Debug, Default, PartialEq)]
+pub struct TaskrcUda {
+    pub name: String,
+    pub kind: Option<String>,
+    pub label: Option<String>,
+    pub values: Option<String>,
+    pub default: Option<String>,
+}
+
+/// what an import saved and what it left behind
+#[derive(Debug, Default)]
+pub struct TaskrcImport {
+    pub urgency: usize,
+    pub reports: usize,
+    pub udas: usize,
+    pub settings: usize,
+    pub skipped: Vec<String>,
+}
+
+impl Taskrc {
+    /// never fails, anything it can't place ends up in `unknown`
+    pub fn parse(input: &str) -> Self {
+        let mut taskrc = Taskrc::default();
+
+        for (idx, line) in input.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(path) = line.strip_prefix("include ") {
+                taskrc.includes.push(path.trim().to_owned());
+                continue;
+            }
+
+            // `rc.` overrides as pasted from a command line, which also allow `:`
+            let setting = match line.strip_prefix("rc.") {
+                Some(line) => line.split_once('=').or_else(|| line.split_once(':')),
+                None => line.split_once('='),
+            };
+            let Some((key, value)) = setting else {
+                taskrc.unknown.push(format!("line {}: {line}", idx + 1));
+                continue;
+            };
+            let (key, value) = (key.trim(), value.trim());
+
+            if !taskrc.push(key, value) {
+                taskrc.unknown.push(format!("line {}: {key}", idx + 1));
+            }
+        }
+
+        taskrc
+    }
+
+    fn push(&mut self, key: &str, value: &str) -> bool {
+        if key.starts_with("urgency.") {
+            self.urgency.push((key.to_owned(), value.to_owned()));
+            return true;
+        }
+        if let Some(rest) = key.strip_prefix("report.") {
+            return self.push_report(rest, value);
+        }
+        if let Some(rest) = key.strip_prefix("uda.") {
+            return self.push_uda(rest, value);
+        }
+
+        let known = matches!(
+            key.split('.').collect_vec().as_slice(),
+            ["dateformat", ..]
+                | ["weekstart"]
+                | ["context"]
+                | ["context", _]
+                | ["context", _, "read" | "write"]
+        );
+        if known {
+            self.settings.push((key.to_owned(), value.to_owned()));
+        }
+        known
+    }
+
+    fn push_report(&mut self, rest: &str, value: &str) -> bool {
+        let Some((name, attribute)) = rest.rsplit_once('.') else {
+            return false;
+        };
+        let report = match self.reports.iter_mut().find(|report| report.name == name) {
+            Some(report) => report,
+            None => {
+                self.reports.push(TaskrcReport {
+                    name: name.to_owned(),
+                    ..Default::default()
+                });
+                self.reports.last_mut().expect("just pushed")
+            }
+        };
+        let field = match attribute {
+            "description" => &mut report.description,
+            "filter" => &mut report.filter,
+            "sort" => &mut report.sort,
+            "columns" => &mut report.columns,
+            // taskbane cards have no column headers
+            "labels" => return true,
+            _ => return false,
+        };
+        *field = Some(value.to_owned());
+        true
+    }
+
+    fn push_uda(&mut self, rest: &str, value: &str) -> bool {
+        let Some((name, attribute)) = rest.split_once('.') else {
+            return false;
+        };
+        match attribute {
+            "type" | "label" | "values" | "default" => {}
+            // taskbane shows the value itself
+            "indicator" => return true,
+            _ => return false,
+        }
+        let uda = match self.udas.iter_mut().find(|uda| uda.name == name) {
+            Some(uda) => uda,
+            None => {
+                self.udas.push(TaskrcUda {
+                    name: name.to_owned(),
+                    ..Default::default()
+                });
+                self.udas.last_mut().expect("just pushed")
+            }
+        };
+        let field = match attribute {
+            "type" => &mut uda.kind,
+            "label" => &mut uda.label,
+            "values" => &mut uda.values,
+            _ => &mut uda.default,
+        };
+        *field = Some(value.to_owned());
+        true
+    }
+}
+
+impl TaskrcReport {
+    /// the filter without taskwarrior's `limit:` pseudo attribute, which
+    /// taskbane would read as a uda
+    pub fn filter(&self) -> Option<String> {
+        let filter = self.filter.as_deref()?;
+        Some(
+            filter
+                .split_whitespace()
+                .filter(|term| !term.starts_with("limit:"))
+                .join(" "),
+        )
+    }
+
+    /// taskwarrior columns as taskbane columns, `due.relative` shows as `due`,
+    /// ones a task card can't show are dropped
+    pub fn columns(&self) -> Option<String> {
+        let columns = self
+            .columns
+            .as_deref()?
+            .split(',')
+            .filter_map(|col| col.trim().split('.').next())
+            .filter(|col| COLUMNS.contains(col))
+            .unique()
+            .join(",");
+        (!columns.is_empty()).then_some(columns)
+    }
+
+    /// sort keys taskbane understands, break markers (`project+/`) removed
+    pub fn sort(&self) -> Option<String> {
+        let sort = self
+            .sort
+            .as_deref()?
+            .split(',')
+            .map(|key| key.trim().trim_end_matches('/'))
+            .filter(|key| key.parse::<Sort>().is_ok())
+            .join(",");
+        (!sort.is_empty()).then_some(sort)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::models::urgency::UrgencyCoefficients;
+
+    const TASKRC: &str = r#"
+# Taskwarrior config
+data.location=~/.task
+include ~/.task/themes/dark-256.theme
+
+urgency.user.tag.home.coefficient=2.5 # chores first
+rc.urgency.due.coefficient:9
+weekstart=monday
+dateformat=Y-M-D
+uda.energy.type=string
+uda.energy.values=high,medium,low
+uda.energy.colour=red
+context.work.read=+work
+report.next.filter=status:pending limit:page
+report.mine.description=My tasks
+report.mine.columns=id,project,due.relative,description.count,urgency
+report.mine.labels=ID,Proj,Due,Desc,Urg
+report.mine.sort=project+/,urgency-
+color.due=red
+nonsense
+"#;
+
+    #[test]
+    fn bad_urgency_values_are_left_out() {
+        let taskrc = Taskrc::parse(
+            "urgency.age.max=0\nurgency.age.max=-5\nurgency.age.max=nan\nurgency.age.max=90\n",
+        );
+        let (saved, skipped): (Vec<_>, Vec<_>) = taskrc
+            .urgency
+            .iter()
+            .partition(|(key, value)| UrgencyCoefficients::check(key, value).is_ok());
+        assert_eq!(saved, [&("urgency.age.max".to_owned(), "90".to_owned())]);
+        assert_eq!(skipped.len(), 3);
+    }
+
+    #[test]
+    fn sorts_lines_into_sections() {
+        let taskrc = Taskrc::parse(TASKRC);
+        assert_eq!(
+            taskrc.urgency,
+            vec![
+                ("urgency.user.tag.home.coefficient".to_owned(), "2.5".to_owned()),
+                ("urgency.due.coefficient".to_owned(), "9".to_owned()),
+            ]
+        );
+        assert_eq!(
+            taskrc.settings.iter().map(|(key, _)| key.as_str()).collect_vec(),
+            vec!["weekstart", "dateformat", "context.work.read"]
+        );
+        assert_eq!(
+            taskrc.udas,
+            vec![TaskrcUda {
+                name: "energy".to_owned(),
+                kind: Some("string".to_owned()),
+                values: Some("high,medium,low".to_owned()),
+                ..Default::default()
+            }]
+        );
+        assert_eq!(taskrc.includes, vec!["~/.task/themes/dark-256.theme"]);
+        assert_eq!(
+            taskrc.unknown,
+            vec![
+                "line 3: data.location",
+                "line 12: uda.energy.colour",
+                "line 19: color.due",
+                "line 20: nonsense"
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_map_to_taskbane() {
+        let taskrc = Taskrc::parse(TASKRC);
+        assert_eq!(taskrc.reports.len(), 2);
+
+        let next = &taskrc.reports[0];
+        assert_eq!(next.name, "next");
+        assert_eq!(next.filter().as_deref(), Some("status:pending"));
+        assert_eq!(next.columns(), None);
+
+        let mine = &taskrc.reports[1];
+        assert_eq!(mine.description.as_deref(), Some("My tasks"));
+        assert_eq!(mine.columns().as_deref(), Some("id,project,due,description,urgency"));
+        assert_eq!(mine.sort().as_deref(), Some("project+,urgency-"));
+    }
+}
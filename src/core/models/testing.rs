@@ -0,0 +1,27 @@
+use chrono_tz::Tz;
+use taskchampion::{storage::inmemory::InMemoryStorage, Operations, Replica, Status, Task};
+use uuid::Uuid;
+
+use super::{task::TaskDto, urgency::UrgencyCoefficients};
+
+/// a pending task read back from a replica, `setup` adds whatever else the test needs
+pub async fn task(description: &str, setup: impl FnOnce(&mut Task, &mut Operations)) -> Task {
+    let mut rep = Replica::new(InMemoryStorage::new());
+    let mut ops = Operations::new();
+    let mut task = rep.create_task(Uuid::new_v4(), &mut ops).await.unwrap();
+    task.set_description(description.to_owned(), &mut ops)
+        .unwrap();
+    task.set_status(Status::Pending, &mut ops).unwrap();
+    setup(&mut task, &mut ops);
+    rep.commit_operations(ops).await.unwrap();
+    rep.get_task(task.get_uuid()).await.unwrap().unwrap()
+}
+
+/// the same task as the pages see it, id 1, default urgency and in UTC
+pub async fn task_dto(
+    description: &str,
+    setup: impl FnOnce(&mut Task, &mut Operations),
+) -> TaskDto {
+    let task = task(description, setup).await;
+    TaskDto::from(1, task, vec![], &UrgencyCoefficients::default(), Tz::UTC)
+}
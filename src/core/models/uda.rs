@@ -0,0 +1,307 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, bail, Error, Result};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use derive_more::Constructor;
+
+use crate::infra::datetime::{from_wall_clock, parse_date};
+
+/// attributes taskchampion or taskbane already give a meaning
+const RESERVED: &[&str] = &[
+    "description",
+    "project",
+    "priority",
+    "status",
+    "uuid",
+    "id",
+    "tags",
+    "depends",
+    "annotations",
+    "due",
+    "wait",
+    "scheduled",
+    "until",
+    "entry",
+    "end",
+    "start",
+    "modified",
+    "recur",
+    "rtype",
+    "mask",
+    "imask",
+    "parent",
+    "urgency",
+];
+
+/// names a uda can't take, taskbane stores some of them as udas itself
+pub fn is_reserved(name: &str) -> bool {
+    RESERVED.contains(&name)
+        || ["tag_", "annotation_", "dep_"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+/// taskwarrior's uda types
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UdaType {
+    String,
+    Numeric,
+    Date,
+    Duration,
+}
+
+impl FromStr for UdaType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "string" => Ok(UdaType::String),
+            "numeric" => Ok(UdaType::Numeric),
+            "date" => Ok(UdaType::Date),
+            "duration" => Ok(UdaType::Duration),
+            _ => bail!("Unknown uda type '{s}'"),
+        }
+    }
+}
+
+impl fmt::Display for UdaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            UdaType::String => "string",
+            UdaType::Numeric => "numeric",
+            UdaType::Date => "date",
+            UdaType::Duration => "duration",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A user defined attribute, the taskbane side of `uda.<name>.*` in a taskrc
+#[derive(Debug, Clone, Constructor)]
+pub struct Uda {
+    pub name: String,
+    pub kind: UdaType,
+    pub label: String,
+    /// allowed values of a string uda, anything goes when empty
+    pub values: Vec<String>,
+    /// prefilled on the create form, empty for none
+    pub default: String,
+    pub coefficient: f64,
+}
+
+impl Uda {
+    pub fn validate(&self) -> Result<()> {
+        let name = self.name.as_str();
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !valid {
+            bail!("Attribute names may only use a-z, 0-9 and '_'");
+        }
+        if is_reserved(name) {
+            bail!("'{name}' is already a task attribute");
+        }
+        if !self.values.is_empty() && self.kind != UdaType::String {
+            bail!("Only string attributes can limit their values");
+        }
+        if !self.default.is_empty() {
+            self.normalize(&self.default, Utc::now().with_timezone(&Tz::UTC))?;
+        }
+        Ok(())
+    }
+
+    pub fn label(&self) -> &str {
+        if self.label.is_empty() {
+            &self.name
+        } else {
+            &self.label
+        }
+    }
+
+    /// a form value as taskwarrior stores it, `None` when it should be cleared.
+    /// dates are wall clock times in the timezone of `now` and become epoch seconds,
+    /// durations are kept as typed once they parse.
+    pub fn normalize(&self, value: &str, now: DateTime<Tz>) -> Result<Option<String>> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        let label = self.label();
+        let value = match self.kind {
+            UdaType::String => {
+                if !self.values.is_empty() && !self.values.iter().any(|allowed| allowed == value) {
+                    bail!("{label} must be one of {}", self.values.join(", "));
+                }
+                value.to_owned()
+            }
+            UdaType::Numeric => {
+                value
+                    .parse::<f64>()
+                    .map_err(|_| anyhow!("{label} must be a number"))?;
+                value.to_owned()
+            }
+            UdaType::Date => parse_date(value, now.naive_local())
+                .map(|date| from_wall_clock(date, now.timezone()))
+                .ok_or_else(|| anyhow!("{label} is not a date"))?
+                .timestamp()
+                .to_string(),
+            UdaType::Duration => {
+                if !is_duration(value) {
+                    bail!("{label} must be a duration like 3h, 2d or PT30M");
+                }
+                value.to_owned()
+            }
+        };
+        Ok(Some(value))
+    }
+
+    /// a stored value for people, dates are stored as epoch seconds and shown in `tz`
+    pub fn display(&self, value: &str, tz: Tz) -> String {
+        match self.kind {
+            UdaType::Date => value
+                .parse::<i64>()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|date| date.with_timezone(&tz).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| value.to_owned()),
+            _ => value.to_owned(),
+        }
+    }
+
+    /// a stored value as a form input that round trips through `normalize`
+    pub fn input(&self, value: &str, tz: Tz) -> String {
+        match self.kind {
+            UdaType::Date => value
+                .parse::<i64>()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0))
+                .map(|date| date.with_timezone(&tz).format("%Y-%m-%dT%H:%M:%S").to_string())
+                .unwrap_or_else(|| value.to_owned()),
+            _ => value.to_owned(),
+        }
+    }
+}
+
+/// `3h`, `90min`, `2d`, `1w` or an ISO 8601 duration like `PT3H`
+fn is_duration(value: &str) -> bool {
+    let value = value.to_lowercase();
+    if let Some(iso) = value.strip_prefix('p') {
+        return !iso.is_empty()
+            && iso
+                .chars()
+                .all(|c| c.is_ascii_digit() || "ymwdthms.".contains(c))
+            && iso.chars().any(|c| c.is_ascii_digit());
+    }
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (count, unit) = value.split_at(split);
+    count.parse::<f64>().is_ok()
+        && matches!(
+            unit.trim(),
+            "s" | "sec"
+                | "secs"
+                | "seconds"
+                | "min"
+                | "mins"
+                | "minutes"
+                | "h"
+                | "hr"
+                | "hrs"
+                | "hours"
+                | "d"
+                | "day"
+                | "days"
+                | "w"
+                | "wk"
+                | "wks"
+                | "weeks"
+                | "mo"
+                | "months"
+                | "y"
+                | "yr"
+                | "yrs"
+                | "years"
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    fn uda(name: &str, kind: UdaType, values: &[&str]) -> Uda {
+        Uda::new(
+            name.to_owned(),
+            kind,
+            String::new(),
+            values.iter().map(|value| value.to_string()).collect(),
+            String::new(),
+            0.0,
+        )
+    }
+
+    #[test]
+    fn names_are_checked() {
+        assert!(uda("estimate", UdaType::Duration, &[]).validate().is_ok());
+        assert!(uda("Energy", UdaType::String, &[]).validate().is_err());
+        assert!(uda("project", UdaType::String, &[]).validate().is_err());
+        assert!(uda("tag_x", UdaType::String, &[]).validate().is_err());
+        assert!(uda("size", UdaType::Numeric, &["1", "2"])
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn values_are_normalized_by_type() {
+        let now = Tz::UTC.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap();
+
+        let energy = uda("energy", UdaType::String, &["high", "low"]);
+        assert_eq!(
+            energy.normalize("low", now).unwrap().as_deref(),
+            Some("low")
+        );
+        assert!(energy.normalize("medium", now).is_err());
+        assert_eq!(energy.normalize("  ", now).unwrap(), None);
+
+        let points = uda("points", UdaType::Numeric, &[]);
+        assert!(points.normalize("3.5", now).is_ok());
+        assert!(points.normalize("lots", now).is_err());
+
+        let estimate = uda("estimate", UdaType::Duration, &[]);
+        assert!(estimate.normalize("3h", now).is_ok());
+        assert!(estimate.normalize("PT30M", now).is_ok());
+        assert!(estimate.normalize("soon", now).is_err());
+
+        let review = uda("review", UdaType::Date, &[]);
+        let stored = review.normalize("2026-01-02", now).unwrap().unwrap();
+        assert_eq!(review.display(&stored, Tz::UTC), "2026-01-02 00:00");
+        assert_eq!(
+            review.normalize(&review.input(&stored, Tz::UTC), now).unwrap(),
+            Some(stored)
+        );
+    }
+
+    #[test]
+    fn dates_are_wall_clock_in_the_timezone() {
+        let tz = Tz::Europe__Berlin;
+        let now = Tz::UTC.with_ymd_and_hms(2026, 1, 1, 9, 0, 0).unwrap().with_timezone(&tz);
+        let review = uda("review", UdaType::Date, &[]);
+
+        let stored = review.normalize("2026-01-02", now).unwrap().unwrap();
+        let midnight = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_hms_opt(23, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert_eq!(stored, midnight.timestamp().to_string());
+        assert_eq!(review.display(&stored, tz), "2026-01-02 00:00");
+        assert_eq!(review.display(&stored, Tz::UTC), "2026-01-01 23:00");
+        assert_eq!(
+            review.normalize(&review.input(&stored, tz), now).unwrap(),
+            Some(stored)
+        );
+    }
+}
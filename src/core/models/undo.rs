@@ -0,0 +1,10 @@
+use taskchampion::Operation;
+
+/// What an undo would reverse, the operations back to the last undo point
+#[derive(Debug, Clone, Default)]
+pub struct UndoPreview {
+    /// kept as they came from the replica, an undo has to hand them back unchanged
+    pub operations: Vec<Operation>,
+    /// (task label, changes) in the order they were made
+    pub tasks: Vec<(String, Vec<String>)>,
+}
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use itertools::Itertools;
+use tracing::info;
+
+/// Urgency coefficients, keyed like taskwarrior's `urgency.*` settings.
+///
+/// Defaults are the values taskbane always used, settings saved by the user
+/// override them one key at a time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrgencyCoefficients {
+    pub due: f64,
+    pub blocking: f64,
+    pub blocked: f64,
+    pub active: f64,
+    pub age: f64,
+    /// days until a task reaches full age urgency
+    pub age_max: f64,
+    pub project: f64,
+    pub tags: f64,
+    pub annotations: f64,
+    pub waiting: f64,
+    /// applies once the scheduled date has passed
+    pub scheduled: f64,
+    pub user_tags: HashMap<String, f64>,
+    pub user_projects: HashMap<String, f64>,
+    /// `name` applies to any value, `name.value` to one value;
+    /// priority lives here like it does in taskwarrior
+    pub udas: HashMap<String, f64>,
+}
+
+impl Default for UrgencyCoefficients {
+    fn default() -> Self {
+        Self {
+            due: 12.0,
+            blocking: 8.0,
+            blocked: -5.0,
+            active: 4.0,
+            age: 2.0,
+            age_max: 365.0,
+            project: 1.0,
+            tags: 1.0,
+            annotations: 1.0,
+            waiting: -3.0,
+            scheduled: 5.0,
+            user_tags: HashMap::from([("next".to_owned(), 15.0), ("fi".to_owned(), 4.0)]),
+            user_projects: HashMap::new(),
+            udas: HashMap::from([
+                ("priority.H".to_owned(), 6.0),
+                ("priority.M".to_owned(), 3.9),
+                ("priority.L".to_owned(), -2.0),
+            ]),
+        }
+    }
+}
+
+impl UrgencyCoefficients {
+    /// defaults overridden by the user's `urgency.*` settings, anything unparsable is skipped
+    pub fn from_settings<'a>(settings: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut coefficients = Self::default();
+        for (key, value) in settings {
+            if !key.starts_with("urgency.") {
+                continue;
+            }
+            if let Err(err) = coefficients.set(key, value) {
+                info!("Skipping urgency setting {key}: {err}");
+            }
+        }
+        coefficients
+    }
+
+    /// whether `value` can be saved under `key`, checked before anything reaches the db
+    /// since a bad `urgency.age.max` would otherwise break every task's urgency
+    pub fn check(key: &str, value: &str) -> Result<()> {
+        Self::default().set(key, value)
+    }
+
+    /// set a coefficient from its taskwarrior key, e.g. `urgency.user.tag.next.coefficient`
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let value: f64 = value
+            .trim()
+            .parse()
+            .map_err(|_| anyhow!("'{value}' is not a number"))?;
+        if !value.is_finite() {
+            bail!("'{value}' is not a number");
+        }
+
+        if key == "urgency.age.max" {
+            if value <= 0. {
+                bail!("urgency.age.max should be more than 0 days");
+            }
+            self.age_max = value;
+            return Ok(());
+        }
+
+        let name = key
+            .strip_prefix("urgency.")
+            .and_then(|key| key.strip_suffix(".coefficient"))
+            .ok_or_else(|| anyhow!("Unknown urgency setting '{key}'"))?;
+
+        let field = match name {
+            "due" => &mut self.due,
+            "blocking" => &mut self.blocking,
+            "blocked" => &mut self.blocked,
+            "active" => &mut self.active,
+            "age" => &mut self.age,
+            "project" => &mut self.project,
+            "tags" => &mut self.tags,
+            "annotations" => &mut self.annotations,
+            "waiting" => &mut self.waiting,
+            "scheduled" => &mut self.scheduled,
+            _ => {
+                let (map, name) = if let Some(tag) = name.strip_prefix("user.tag.") {
+                    (&mut self.user_tags, tag)
+                } else if let Some(project) = name.strip_prefix("user.project.") {
+                    (&mut self.user_projects, project)
+                } else if let Some(uda) = name.strip_prefix("uda.") {
+                    (&mut self.udas, uda)
+                } else {
+                    bail!("Unknown urgency setting '{key}'");
+                };
+                if name.is_empty() {
+                    bail!("Missing name in '{key}'");
+                }
+                map.insert(name.to_owned(), value);
+                return Ok(());
+            }
+        };
+        *field = value;
+
+        Ok(())
+    }
+
+    /// every coefficient with its taskwarrior key, sorted by key
+    pub fn entries(&self) -> Vec<(String, f64)> {
+        let fixed = [
+            ("due", self.due),
+            ("blocking", self.blocking),
+            ("blocked", self.blocked),
+            ("active", self.active),
+            ("age", self.age),
+            ("project", self.project),
+            ("tags", self.tags),
+            ("annotations", self.annotations),
+            ("waiting", self.waiting),
+            ("scheduled", self.scheduled),
+        ]
+        .into_iter()
+        .map(|(name, value)| (format!("urgency.{name}.coefficient"), value));
+
+        let named = |prefix: &'static str, map: &HashMap<String, f64>| {
+            map.iter()
+                .map(move |(name, value)| (format!("urgency.{prefix}.{name}.coefficient"), *value))
+                .collect::<Vec<_>>()
+        };
+
+        fixed
+            .chain([("urgency.age.max".to_owned(), self.age_max)])
+            .chain(named("user.tag", &self.user_tags))
+            .chain(named("user.project", &self.user_projects))
+            .chain(named("uda", &self.udas))
+            .sorted_by(|a, b| a.0.cmp(&b.0))
+            .collect()
+    }
+
+    pub fn tag(&self, tag: &str) -> f64 {
+        self.user_tags.get(tag).copied().unwrap_or_default()
+    }
+
+    /// coefficients of every configured project the task's project falls under
+    pub fn project(&self, project: &str) -> f64 {
+        self.user_projects
+            .iter()
+            .filter(|(name, _)| {
+                project == name.as_str()
+                    || project
+                        .strip_prefix(name.as_str())
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .map(|(_, value)| value)
+            .sum()
+    }
+
+    /// `name` matches any value of the uda, `name.value` only that value
+    pub fn uda(&self, name: &str, value: &str) -> f64 {
+        self.udas
+            .iter()
+            .filter_map(|(key, coefficient)| match key.split_once('.') {
+                Some((uda, expected)) => {
+                    (uda == name && expected.eq_ignore_ascii_case(value)).then_some(coefficient)
+                }
+                None => (key == name).then_some(coefficient),
+            })
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_override_defaults() {
+        let coefficients = UrgencyCoefficients::from_settings([
+            ("urgency.due.coefficient", "9.5"),
+            ("urgency.user.tag.next.coefficient", "20"),
+            ("urgency.user.project.work.coefficient", "2"),
+            ("urgency.uda.estimate.coefficient", "1.5"),
+            ("urgency.uda.priority.H.coefficient", "7"),
+            ("urgency.bogus.coefficient", "1"),
+            ("urgency.age.coefficient", "lots"),
+            ("report.next.filter", "+next"),
+        ]);
+        assert_eq!(coefficients.due, 9.5);
+        assert_eq!(coefficients.age, 2.0);
+        assert_eq!(coefficients.tag("next"), 20.0);
+        assert_eq!(coefficients.tag("fi"), 4.0);
+        assert_eq!(coefficients.tag("home"), 0.0);
+        assert_eq!(coefficients.project("work.meetings"), 2.0);
+        assert_eq!(coefficients.project("workshop"), 0.0);
+        assert_eq!(coefficients.uda("estimate", "3h"), 1.5);
+        assert_eq!(coefficients.uda("priority", "h"), 7.0);
+        assert_eq!(coefficients.uda("priority", "m"), 3.9);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let mut coefficients = UrgencyCoefficients::default();
+        assert!(coefficients.set("urgency.colour.coefficient", "1").is_err());
+        assert!(coefficients.set("urgency.user.tag..coefficient", "1").is_err());
+        assert!(coefficients.set("color.due", "1").is_err());
+        assert!(coefficients.set("urgency.due.coefficient", "x").is_err());
+    }
+
+    #[test]
+    fn bad_values_are_rejected() {
+        let mut coefficients = UrgencyCoefficients::default();
+        assert!(coefficients.set("urgency.age.max", "0").is_err());
+        assert!(coefficients.set("urgency.age.max", "-30").is_err());
+        assert!(coefficients.set("urgency.age.max", "NaN").is_err());
+        assert!(coefficients.set("urgency.due.coefficient", "nan").is_err());
+        assert!(coefficients.set("urgency.due.coefficient", "inf").is_err());
+        assert_eq!(coefficients, UrgencyCoefficients::default());
+
+        // negative coefficients are fine, only the age span has to be positive
+        assert!(coefficients.set("urgency.due.coefficient", "-1").is_ok());
+        assert!(coefficients.set("urgency.age.max", "30").is_ok());
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let defaults = UrgencyCoefficients::default();
+        let entries = defaults.entries();
+        assert!(entries.contains(&("urgency.user.tag.next.coefficient".to_owned(), 15.0)));
+        let reloaded = UrgencyCoefficients::from_settings(
+            entries
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.to_string()))
+                .collect::<Vec<_>>()
+                .iter()
+                .map(|(key, value)| (*key, value.as_str())),
+        );
+        assert_eq!(reloaded, defaults);
+    }
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::core::models::account::TaskAccount;
+
+#[async_trait]
+pub trait TaskAccountRepository: Send + Sync {
+    /// false without a key to seal the credentials with
+    fn enabled(&self) -> bool;
+    /// `None` for users on the shared database
+    async fn account(&self, user_id: Uuid) -> Result<Option<TaskAccount>>;
+    /// stores the account and moves the user to a replica syncing with it
+    async fn set_account(&self, user_id: Uuid, account: TaskAccount) -> Result<()>;
+    /// back to the shared database, the user's own replica is kept
+    async fn remove_account(&self, user_id: Uuid) -> Result<()>;
+    /// opens the replicas of every stored account, at startup
+    async fn open_all(&self) -> Result<usize>;
+}
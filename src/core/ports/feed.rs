@@ -0,0 +1,13 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait FeedRepository: Send + Sync {
+    async fn token(&self, user_id: Uuid) -> Result<Option<Uuid>>;
+    /// the user a feed url belongs to, `None` once it's been revoked
+    async fn user(&self, token: Uuid) -> Result<Option<Uuid>>;
+    /// replaces the user's token, so the old url stops working
+    async fn set_token(&self, user_id: Uuid, token: Uuid) -> Result<()>;
+    async fn revoke(&self, user_id: Uuid) -> Result<()>;
+}
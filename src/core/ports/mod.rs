@@ -1,3 +1,10 @@
+pub mod account;
 pub mod auth;
+pub mod feed;
+pub mod report;
+pub mod search;
+pub mod settings;
+pub mod sync;
 pub mod task;
+pub mod uda;
 pub mod user;
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::core::models::report::Report;
+
+#[async_trait]
+pub trait ReportRepository: Send + Sync {
+    async fn list(&self, user_id: Uuid) -> Result<Vec<Report>>;
+    async fn get(&self, user_id: Uuid, name: &str) -> Result<Option<Report>>;
+    async fn save(&self, user_id: Uuid, report: &Report) -> Result<()>;
+    async fn delete(&self, user_id: Uuid, name: &str) -> Result<()>;
+}
@@ -0,0 +1,16 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait TaskSearchRepository: Send + Sync {
+    /// uuids of pending tasks matching every word of `query`, best match first,
+    /// completed tasks are included when asked
+    async fn search(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_completed: bool,
+        limit: u32,
+    ) -> Result<Vec<Uuid>>;
+}
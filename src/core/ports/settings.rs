@@ -0,0 +1,11 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait SettingsRepository: Send + Sync {
+    /// all settings for a user as (key, value), sorted by key
+    async fn list(&self, user_id: Uuid) -> Result<Vec<(String, String)>>;
+    async fn set(&self, user_id: Uuid, key: &str, value: &str) -> Result<()>;
+    async fn delete(&self, user_id: Uuid, key: &str) -> Result<()>;
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::core::models::sync::SyncStatus;
+
+#[async_trait]
+pub trait SyncRepository: Send + Sync {
+    /// of the replica `user_id` works on
+    async fn status(&self, user_id: Uuid) -> Result<SyncStatus>;
+    /// wakes the replica's sync and waits a little for it, the status after
+    async fn sync_now(&self, user_id: Uuid) -> Result<SyncStatus>;
+}
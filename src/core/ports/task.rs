@@ -1,29 +1,105 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use derive_more::Constructor;
-use taskchampion::{Annotation, Tag, Task};
+use taskchampion::{Annotation, Operation, Status, Tag, Task};
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
-#[derive(Debug, Constructor)]
+#[derive(Debug)]
 pub struct CreateTaskInput {
     pub description: String,
     pub priority: String,
+    pub project: Option<String>,
     pub deps: Vec<Uuid>,
     pub tags: Vec<Tag>,
     pub due: Option<DateTime<Utc>>,
+    /// hidden from the pending list until then
+    pub wait: Option<DateTime<Utc>>,
+    pub scheduled: Option<DateTime<Utc>>,
+    /// makes the task a recurring template, a taskwarrior `recur` value
+    pub recur: Option<String>,
+    pub until: Option<DateTime<Utc>>,
+    /// schema udas from the form, `None` clears the attribute
+    pub udas: Vec<(String, Option<String>)>,
 }
 
+/// one change applied to many tasks at once
+#[derive(Debug, Clone)]
+pub enum BulkAction {
+    Done,
+    Delete,
+    Start,
+    Stop,
+    /// a completed task back to pending
+    Reopen,
+    Wait(Option<DateTime<Utc>>),
+    AddTag(Tag),
+    RemoveTag(Tag),
+    Priority(String),
+    Due(Option<DateTime<Utc>>),
+    Project(Option<String>),
+}
+
+/// Every method works on the replica of `user_id`
 #[async_trait]
 pub trait TaskRepository: Send + Sync {
-    async fn get_task(&self, uuid: Uuid) -> Result<Option<Task>>;
-    async fn get_task_meta(&self, uuid: Uuid, deps: Vec<Uuid>) -> Result<(usize, Vec<usize>)>;
-    async fn list(&self) -> Result<Vec<(usize, Task, Vec<usize>)>>;
+    async fn get_task(&self, user_id: Uuid, uuid: Uuid) -> Result<Option<Task>>;
+    async fn get_task_meta(
+        &self,
+        user_id: Uuid,
+        uuid: Uuid,
+        deps: Vec<Uuid>,
+    ) -> Result<(usize, Vec<usize>)>;
+    async fn list(&self, user_id: Uuid) -> Result<Vec<(usize, Task, Vec<usize>)>>;
+    /// every task regardless of status, tasks outside the working set get id 0
+    async fn list_all(&self, user_id: Uuid) -> Result<Vec<(usize, Task, Vec<usize>)>>;
+    /// all tasks with `status`, tasks outside the working set get id 0
+    async fn list_with_status(
+        &self,
+        user_id: Uuid,
+        status: Status,
+    ) -> Result<Vec<(usize, Task, Vec<usize>)>>;
     async fn find(
         &self,
+        user_id: Uuid,
         filter: &(dyn for<'a> Fn(&'a Task) -> bool + Send + Sync),
     ) -> Result<Option<Task>>;
-    async fn mark_task_done(&self, uuid: Uuid) -> Result<()>;
-    async fn create_task(&self, input: CreateTaskInput) -> Result<usize>;
-    async fn annotate(&self, uuid: Uuid, annotation: Annotation) -> Result<()>;
+    async fn mark_task_done(&self, user_id: Uuid, uuid: Uuid) -> Result<()>;
+    async fn start_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()>;
+    async fn stop_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()>;
+    async fn delete_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()>;
+    /// move a deleted task back to pending
+    async fn restore_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()>;
+    /// move a completed task back to pending
+    async fn reopen_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()>;
+    async fn create_task(&self, user_id: Uuid, input: CreateTaskInput) -> Result<usize>;
+    async fn update_task(&self, user_id: Uuid, uuid: Uuid, input: CreateTaskInput) -> Result<()>;
+    async fn annotate(&self, user_id: Uuid, uuid: Uuid, annotation: Annotation) -> Result<()>;
+    /// create the children of a recurring template missing by `now` and extend its mask
+    /// to cover them, returns how many were made. Works from the template as it is under
+    /// the replica's lock, so two callers at once don't make the same children
+    async fn create_recurrences(
+        &self,
+        user_id: Uuid,
+        template: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<usize>;
+    /// applies `actions` in order to every task in one commit, so it syncs and undoes as a unit
+    async fn bulk_update(
+        &self,
+        user_id: Uuid,
+        uuids: &[Uuid],
+        actions: &[BulkAction],
+    ) -> Result<()>;
+    /// every operation ever applied to the task, oldest first
+    async fn task_operations(&self, user_id: Uuid, uuid: Uuid) -> Result<Vec<Operation>>;
+    /// unsynced operations back to the last undo point
+    async fn undo_operations(&self, user_id: Uuid) -> Result<Vec<Operation>>;
+    /// reverse `operations`, false when they no longer match the unsynced ones
+    async fn undo(&self, user_id: Uuid, operations: Vec<Operation>) -> Result<bool>;
+    /// uuids of tasks changed from now on, by a commit here or by a sync
+    fn subscribe(&self, user_id: Uuid) -> broadcast::Receiver<Vec<Uuid>>;
+    /// a user for every replica, the nil uuid for the one shared by users
+    /// without an account of their own
+    fn owners(&self) -> Vec<Uuid>;
 }
@@ -0,0 +1,12 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::core::models::uda::Uda;
+
+#[async_trait]
+pub trait UdaRepository: Send + Sync {
+    async fn list(&self, user_id: Uuid) -> Result<Vec<Uda>>;
+    async fn save(&self, user_id: Uuid, uda: &Uda) -> Result<()>;
+    async fn delete(&self, user_id: Uuid, name: &str) -> Result<()>;
+}
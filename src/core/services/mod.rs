@@ -1,4 +1,7 @@
 mod auth;
+mod report;
+mod settings;
+mod sync;
 mod task;
 mod user;
 
@@ -9,6 +12,9 @@ use webauthn_rs::Webauthn;
 use crate::core::ports;
 
 pub use auth::AuthService;
+pub use report::{ReportInput, ReportService};
+pub use settings::SettingsService;
+pub use sync::SyncService;
 pub use task::TaskService;
 pub use user::UserService;
 
@@ -16,6 +22,13 @@ pub struct CreateServiceParams {
     pub user_repo: Arc<dyn ports::user::UserRepository>,
     pub auth_repo: Arc<dyn ports::auth::AuthRepository>,
     pub task_repo: Arc<dyn ports::task::TaskRepository>,
+    pub report_repo: Arc<dyn ports::report::ReportRepository>,
+    pub settings_repo: Arc<dyn ports::settings::SettingsRepository>,
+    pub search_repo: Arc<dyn ports::search::TaskSearchRepository>,
+    pub uda_repo: Arc<dyn ports::uda::UdaRepository>,
+    pub feed_repo: Arc<dyn ports::feed::FeedRepository>,
+    pub sync_repo: Arc<dyn ports::sync::SyncRepository>,
+    pub account_repo: Arc<dyn ports::account::TaskAccountRepository>,
     pub webauthn: Arc<Webauthn>,
 }
 
@@ -24,13 +37,37 @@ pub fn create_services(
         user_repo,
         auth_repo,
         task_repo,
+        report_repo,
+        settings_repo,
+        search_repo,
+        uda_repo,
+        feed_repo,
+        sync_repo,
+        account_repo,
         webauthn,
     }: CreateServiceParams,
-) -> (user::UserService, task::TaskService, auth::AuthService) {
+) -> (
+    user::UserService,
+    task::TaskService,
+    auth::AuthService,
+    report::ReportService,
+    settings::SettingsService,
+    sync::SyncService,
+) {
     let user_service = user::UserService::new(user_repo);
+    let report_service = report::ReportService::new(report_repo);
+    let settings_service = settings::SettingsService::new(
+        settings_repo,
+        uda_repo,
+        feed_repo,
+        report_service.clone(),
+    );
     (
         user_service.clone(),
-        task::TaskService::new(task_repo),
+        task::TaskService::new(task_repo, search_repo, settings_service.clone()),
         auth::AuthService::new(auth_repo, webauthn, user_service),
+        report_service,
+        settings_service,
+        sync::SyncService::new(sync_repo, account_repo),
     )
 }
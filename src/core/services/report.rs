@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use chrono_tz::Tz;
+use derive_more::Constructor;
+use uuid::Uuid;
+
+use crate::core::{
+    models::{filter::Filter, report::Report},
+    ports::report::ReportRepository,
+};
+
+#[derive(Debug, Constructor)]
+pub struct ReportInput {
+    pub name: String,
+    pub description: String,
+    pub filter: String,
+    pub sort: String,
+    pub columns: String,
+}
+
+#[derive(Constructor, Clone)]
+pub struct ReportService {
+    repo: Arc<dyn ReportRepository>,
+}
+
+impl ReportService {
+    /// built-in reports first, replaced by any the user saved under the same name
+    pub async fn list(&self, user_id: Uuid) -> Result<Vec<Report>> {
+        let mut saved = self.repo.list(user_id).await?;
+        let mut reports: Vec<Report> = Report::builtins()
+            .into_iter()
+            .map(|builtin| {
+                match saved.iter().position(|report| report.name == builtin.name) {
+                    Some(idx) => saved.remove(idx),
+                    None => builtin,
+                }
+            })
+            .collect();
+        reports.append(&mut saved);
+        Ok(reports)
+    }
+
+    pub async fn get(&self, user_id: Uuid, name: &str) -> Result<Report> {
+        if let Some(report) = self.repo.get(user_id, name).await? {
+            return Ok(report);
+        }
+        Report::builtins()
+            .into_iter()
+            .find(|report| report.name == name)
+            .ok_or(anyhow!("No report named {name}"))
+    }
+
+    pub async fn save(&self, user_id: Uuid, input: ReportInput) -> Result<Report> {
+        let name = input.name.trim().to_lowercase();
+        Report::validate_name(&name)?;
+        // only checking that it parses, dates are read again on every use
+        Filter::parse(&input.filter, Utc::now().with_timezone(&Tz::UTC))?;
+
+        let report = Report::new(
+            name,
+            input.description.trim().to_owned(),
+            input.filter.trim().to_owned(),
+            input.sort.parse()?,
+            input.columns.parse()?,
+            false,
+        );
+        self.repo.save(user_id, &report).await?;
+
+        Ok(report)
+    }
+
+    /// removes a saved report, built-ins go back to their defaults
+    pub async fn delete(&self, user_id: Uuid, name: &str) -> Result<()> {
+        self.repo.delete(user_id, name).await
+    }
+}
@@ -0,0 +1,237 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::Weekday;
+use chrono_tz::Tz;
+use derive_more::Constructor;
+use uuid::Uuid;
+
+use super::{ReportInput, ReportService};
+use crate::core::{
+    models::{
+        report::Columns,
+        taskrc::{Taskrc, TaskrcImport},
+        uda::{Uda, UdaType},
+        urgency::UrgencyCoefficients,
+    },
+    ports::{feed::FeedRepository, settings::SettingsRepository, uda::UdaRepository},
+};
+
+#[derive(Constructor, Clone)]
+pub struct SettingsService {
+    repo: Arc<dyn SettingsRepository>,
+    uda_repo: Arc<dyn UdaRepository>,
+    feed_repo: Arc<dyn FeedRepository>,
+    reports: ReportService,
+}
+
+impl SettingsService {
+    /// defaults, then coefficients from the uda schema, then the user's settings
+    pub async fn urgency(&self, user_id: Uuid) -> Result<UrgencyCoefficients> {
+        let schema = self
+            .uda_repo
+            .list(user_id)
+            .await?
+            .into_iter()
+            .filter(|uda| uda.coefficient != 0.0)
+            .map(|uda| {
+                (
+                    format!("urgency.uda.{}.coefficient", uda.name),
+                    uda.coefficient.to_string(),
+                )
+            });
+        let settings: Vec<_> = schema.chain(self.repo.list(user_id).await?).collect();
+
+        Ok(UrgencyCoefficients::from_settings(
+            settings
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        ))
+    }
+
+    pub async fn udas(&self, user_id: Uuid) -> Result<Vec<Uda>> {
+        self.uda_repo.list(user_id).await
+    }
+
+    pub async fn save_uda(&self, user_id: Uuid, uda: Uda) -> Result<Uda> {
+        uda.validate()?;
+        self.uda_repo.save(user_id, &uda).await?;
+        Ok(uda)
+    }
+
+    /// values already on tasks stay, like removing a uda from a taskrc
+    pub async fn delete_uda(&self, user_id: Uuid, name: &str) -> Result<()> {
+        self.uda_repo.delete(user_id, name).await
+    }
+
+    /// where the user's dates are wall clock times, UTC until they pick one since
+    /// that's how dates were stored before there was a choice
+    pub async fn timezone(&self, user_id: Uuid) -> Result<Tz> {
+        let settings = self.repo.list(user_id).await?;
+        Ok(settings
+            .iter()
+            .find(|(key, _)| key == "timezone")
+            .and_then(|(_, value)| value.parse().ok())
+            .unwrap_or(Tz::UTC))
+    }
+
+    /// the first day of calendar weeks, `weekstart` from a taskrc
+    pub async fn weekstart(&self, user_id: Uuid) -> Result<Weekday> {
+        let settings = self.repo.list(user_id).await?;
+        let sunday = settings
+            .iter()
+            .any(|(key, value)| key == "weekstart" && value.eq_ignore_ascii_case("sunday"));
+        Ok(if sunday { Weekday::Sun } else { Weekday::Mon })
+    }
+
+    pub async fn set_calendar(&self, user_id: Uuid, timezone: &str, weekstart: &str) -> Result<()> {
+        let timezone = timezone.trim();
+        timezone
+            .parse::<Tz>()
+            .map_err(|_| anyhow!("Unknown timezone '{timezone}'"))?;
+        let weekstart = weekstart.trim().to_lowercase();
+        if !["sunday", "monday"].contains(&weekstart.as_str()) {
+            bail!("Weeks can start on sunday or monday");
+        }
+        self.repo.set(user_id, "timezone", timezone).await?;
+        self.repo.set(user_id, "weekstart", &weekstart).await
+    }
+
+    pub async fn feed_token(&self, user_id: Uuid) -> Result<Option<Uuid>> {
+        self.feed_repo.token(user_id).await
+    }
+
+    /// a fresh secret feed url, any earlier one stops working
+    pub async fn reset_feed_token(&self, user_id: Uuid) -> Result<Uuid> {
+        let token = Uuid::new_v4();
+        self.feed_repo.set_token(user_id, token).await?;
+        Ok(token)
+    }
+
+    pub async fn revoke_feed(&self, user_id: Uuid) -> Result<()> {
+        self.feed_repo.revoke(user_id).await
+    }
+
+    /// the user a feed token belongs to
+    pub async fn feed_user(&self, token: Uuid) -> Result<Option<Uuid>> {
+        self.feed_repo.user(token).await
+    }
+
+    /// keys the user changed from the defaults
+    pub async fn overrides(&self, user_id: Uuid) -> Result<Vec<(String, String)>> {
+        self.repo.list(user_id).await
+    }
+
+    pub async fn set_urgency(&self, user_id: Uuid, key: &str, value: &str) -> Result<()> {
+        let key = key.trim();
+        UrgencyCoefficients::check(key, value)?;
+        self.repo.set(user_id, key, value.trim()).await
+    }
+
+    /// saves everything usable from a `.taskrc`, bad values are skipped rather
+    /// than failing the whole import
+    pub async fn import_taskrc(&self, user_id: Uuid, input: &str) -> Result<TaskrcImport> {
+        let taskrc = Taskrc::parse(input);
+        let mut import = TaskrcImport::default();
+        import.skipped.extend(
+            taskrc
+                .includes
+                .iter()
+                .map(|path| format!("include {path} (not followed)")),
+        );
+        import.skipped.extend(taskrc.unknown);
+
+        for (key, value) in taskrc.urgency {
+            if let Err(err) = UrgencyCoefficients::check(&key, &value) {
+                import.skipped.push(format!("{key}: {err}"));
+                continue;
+            }
+            self.repo.set(user_id, &key, &value).await?;
+            import.urgency += 1;
+        }
+
+        for (key, value) in taskrc.settings {
+            if key == "weekstart" && !["sunday", "monday"].contains(&value.to_lowercase().as_str()) {
+                import.skipped.push(format!("{key}: must be sunday or monday"));
+                continue;
+            }
+            self.repo.set(user_id, &key, &value).await?;
+            import.settings += 1;
+        }
+
+        let existing = self.uda_repo.list(user_id).await?;
+        for uda in taskrc.udas {
+            let base = existing.iter().find(|base| base.name == uda.name);
+            let kind = match uda.kind.as_deref().map(str::parse).transpose() {
+                Ok(kind) => kind
+                    .or(base.map(|base| base.kind))
+                    .unwrap_or(UdaType::String),
+                Err(err) => {
+                    import.skipped.push(format!("uda.{}: {err}", uda.name));
+                    continue;
+                }
+            };
+            let input = Uda::new(
+                uda.name.clone(),
+                kind,
+                uda.label
+                    .or_else(|| base.map(|base| base.label.clone()))
+                    .unwrap_or_default(),
+                uda.values
+                    .map(|values| {
+                        values
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|value| !value.is_empty())
+                            .map(str::to_owned)
+                            .collect()
+                    })
+                    .or_else(|| base.map(|base| base.values.clone()))
+                    .unwrap_or_default(),
+                uda.default
+                    .or_else(|| base.map(|base| base.default.clone()))
+                    .unwrap_or_default(),
+                base.map_or(0.0, |base| base.coefficient),
+            );
+            match self.save_uda(user_id, input).await {
+                Ok(_) => import.udas += 1,
+                Err(err) => import.skipped.push(format!("uda.{}: {err}", uda.name)),
+            }
+        }
+
+        for report in taskrc.reports {
+            // partial definitions only change what they mention, like in taskwarrior
+            let base = self.reports.get(user_id, &report.name).await.ok();
+            let input = ReportInput::new(
+                report.name.clone(),
+                report
+                    .description
+                    .clone()
+                    .or_else(|| base.as_ref().map(|base| base.description.clone()))
+                    .unwrap_or_default(),
+                report
+                    .filter()
+                    .or_else(|| base.as_ref().map(|base| base.filter.clone()))
+                    .unwrap_or_default(),
+                report
+                    .sort()
+                    .or_else(|| base.as_ref().map(|base| base.sort.to_string()))
+                    .unwrap_or_else(|| "urgency-".to_owned()),
+                report
+                    .columns()
+                    .or_else(|| base.as_ref().map(|base| base.columns.to_string()))
+                    .unwrap_or_else(|| Columns::default().to_string()),
+            );
+            match self.reports.save(user_id, input).await {
+                Ok(_) => import.reports += 1,
+                Err(err) => import.skipped.push(format!("report.{}: {err}", report.name)),
+            }
+        }
+
+        Ok(import)
+    }
+
+    pub async fn reset(&self, user_id: Uuid, key: &str) -> Result<()> {
+        self.repo.delete(user_id, key).await
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use derive_more::Constructor;
+use uuid::Uuid;
+
+use crate::core::{
+    models::{account::TaskAccount, sync::SyncStatus},
+    ports::{account::TaskAccountRepository, sync::SyncRepository},
+};
+
+#[derive(Constructor, Clone)]
+pub struct SyncService {
+    repo: Arc<dyn SyncRepository>,
+    account_repo: Arc<dyn TaskAccountRepository>,
+}
+
+impl SyncService {
+    pub async fn status(&self, user_id: Uuid) -> Result<SyncStatus> {
+        self.repo.status(user_id).await
+    }
+
+    pub async fn sync_now(&self, user_id: Uuid) -> Result<SyncStatus> {
+        self.repo.sync_now(user_id).await
+    }
+
+    pub fn accounts_enabled(&self) -> bool {
+        self.account_repo.enabled()
+    }
+
+    pub async fn account(&self, user_id: Uuid) -> Result<Option<TaskAccount>> {
+        self.account_repo.account(user_id).await
+    }
+
+    pub async fn set_account(
+        &self,
+        user_id: Uuid,
+        url: &str,
+        client_id: &str,
+        encryption_secret: &str,
+    ) -> Result<TaskAccount> {
+        let account = TaskAccount::new(url, client_id, encryption_secret)?;
+        self.account_repo
+            .set_account(user_id, account.clone())
+            .await?;
+        Ok(account)
+    }
+
+    pub async fn remove_account(&self, user_id: Uuid) -> Result<()> {
+        self.account_repo.remove_account(user_id).await
+    }
+
+    pub async fn open_accounts(&self) -> Result<usize> {
+        self.account_repo.open_all().await
+    }
+}
@@ -1,98 +1,675 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use anyhow::{anyhow, Result};
-use chrono::{Local, NaiveDateTime};
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
 use derive_more::Constructor;
 use itertools::Itertools;
-use taskchampion::{Annotation, Tag, Task};
+use taskchampion::{Annotation, Operation, Status, Tag, Task};
+use tokio::sync::broadcast;
 use tracing::info;
 use uuid::Uuid;
 
 use crate::{
     app::drivers::task::CreateTaskQuery,
     core::{
-        models::task::TaskDto,
-        ports::task::{CreateTaskInput, TaskRepository},
+        models::{
+            board::{BoardColumn, Grouping, TaskState},
+            calendar::{Calendar, CalendarView},
+            dependency::{DependencyGraph, TaskDependencies},
+            filter::Filter,
+            project::ProjectSummary,
+            recurrence::Period,
+            task::TaskDto,
+            history::{self, HistoryEntry},
+            ical,
+            uda::Uda,
+            undo::UndoPreview,
+        },
+        ports::{
+            search::TaskSearchRepository,
+            task::{BulkAction, CreateTaskInput, TaskRepository},
+        },
+        services::SettingsService,
+    },
+    infra::{
+        datetime::{from_wall_clock, parse_date},
+        error::ApiError,
     },
-    infra::datetime::parse_date,
 };
 
 #[derive(Constructor, Clone)]
 pub struct TaskService {
     repo: Arc<dyn TaskRepository>,
+    search: Arc<dyn TaskSearchRepository>,
+    settings: SettingsService,
 }
 
+/// more than a phone screen of results isn't useful
+const SEARCH_LIMIT: u32 = 50;
+/// how far back the done column of the board goes
+const BOARD_DONE_DAYS: i64 = 7;
+
 impl TaskService {
-    pub async fn get_task(&self, uuid: Uuid) -> Result<TaskDto> {
-        let task = self
-            .repo
-            .get_task(uuid)
+    pub async fn get_task(&self, user_id: Uuid, uuid: Uuid) -> Result<TaskDto> {
+        self.find_task(user_id, uuid)
             .await?
-            .ok_or(anyhow!("No task found for uuid"))?;
+            .ok_or(anyhow!("No task found for uuid"))
+    }
+
+    /// the task, `None` once it's gone from the replica
+    pub async fn find_task(&self, user_id: Uuid, uuid: Uuid) -> Result<Option<TaskDto>> {
+        let Some(task) = self.repo.get_task(user_id, uuid).await? else {
+            return Ok(None);
+        };
 
         let deps = task.get_dependencies().collect::<Vec<Uuid>>();
-        let (id, deps) = self.repo.get_task_meta(task.get_uuid(), deps).await?;
+        let (id, deps) = self.repo.get_task_meta(user_id, task.get_uuid(), deps).await?;
+        let coeffs = self.settings.urgency(user_id).await?;
+        let tz = self.timezone(user_id).await?;
 
-        Ok(TaskDto::from(id, task, deps))
+        Ok(Some(TaskDto::from(id, task, deps, &coeffs, tz)))
     }
-    pub async fn list(&self) -> Result<Vec<TaskDto>> {
+    /// pending tasks, or every task when the filter asks for other statuses.
+    /// Deleted ones only when the filter names them
+    pub async fn list(&self, user_id: Uuid, filter: Option<&Filter>) -> Result<Vec<TaskDto>> {
+        let coeffs = self.settings.urgency(user_id).await?;
+        let tz = self.timezone(user_id).await?;
+        let tasks = match filter {
+            Some(filter) if filter.needs_all_tasks() => self.repo.list_all(user_id).await?,
+            _ => self.repo.list(user_id).await?,
+        };
+        let deleted = filter.is_some_and(Filter::includes_deleted);
+        let tasks = tasks
+            .into_iter()
+            .filter(|(_, task, _)| deleted || task.get_status() != Status::Deleted)
+            .filter(|(id, task, _)| filter.is_none_or(|filter| filter.matches(*id, task)))
+            .map(|(id, task, deps)| TaskDto::from(id, task, deps, &coeffs, tz))
+            .sorted_by_key(|task| -(task.urgency * 100.) as i64)
+            .collect();
+        Ok(tasks)
+    }
+    /// pending tasks matching `query` best match first, optionally completed ones too
+    pub async fn search(
+        &self,
+        user_id: Uuid,
+        query: &str,
+        include_completed: bool,
+    ) -> Result<Vec<TaskDto>> {
+        let coeffs = self.settings.urgency(user_id).await?;
+        let tz = self.timezone(user_id).await?;
+        let uuids = self
+            .search
+            .search(user_id, query, include_completed, SEARCH_LIMIT)
+            .await?;
+
+        let mut tasks = Vec::with_capacity(uuids.len());
+        for uuid in uuids {
+            let Some(task) = self.repo.get_task(user_id, uuid).await? else {
+                continue;
+            };
+            let deps = task.get_dependencies().collect::<Vec<Uuid>>();
+            let (id, deps) = self.repo.get_task_meta(user_id, uuid, deps).await?;
+            tasks.push(TaskDto::from(id, task, deps, &coeffs, tz));
+        }
+        Ok(tasks)
+    }
+    /// tasks with `status`, most recently ended first
+    pub async fn list_with_status(&self, user_id: Uuid, status: Status) -> Result<Vec<TaskDto>> {
+        let coeffs = self.settings.urgency(user_id).await?;
+        let tz = self.timezone(user_id).await?;
         let tasks = self
             .repo
-            .list()
+            .list_with_status(user_id, status)
             .await?
             .into_iter()
-            .map(|(id, task, deps)| TaskDto::from(id, task, deps))
-            .sorted_by_key(|task| -(task.urgency * 100.) as i64)
+            .map(|(id, task, deps)| TaskDto::from(id, task, deps, &coeffs, tz))
+            .sorted_by(|a, b| b.end_at.cmp(&a.end_at))
             .collect();
         Ok(tasks)
     }
+    /// hidden pending tasks grouped by the user's day they wake up on, soonest first
+    pub async fn list_waiting(&self, user_id: Uuid) -> Result<Vec<(NaiveDate, Vec<TaskDto>)>> {
+        let tasks = self.list_with_status(user_id, Status::Pending).await?;
+        let days = tasks
+            .into_iter()
+            .filter(|task| task.wait.is_some())
+            .sorted_by(|a, b| a.wait_at.cmp(&b.wait_at))
+            .chunk_by(|task| {
+                task.wait_at
+                    .map(|wait| task.local(&wait).date_naive())
+                    .unwrap_or_default()
+            })
+            .into_iter()
+            .map(|(day, tasks)| (day, tasks.collect()))
+            .collect();
+        Ok(days)
+    }
+    /// completed tasks grouped by the user's day they were completed on, newest first
+    pub async fn list_completed(&self, user_id: Uuid) -> Result<Vec<(NaiveDate, Vec<TaskDto>)>> {
+        let tasks = self.list_with_status(user_id, Status::Completed).await?;
+        let days = tasks
+            .into_iter()
+            .chunk_by(|task| {
+                task.end_at
+                    .map(|end| task.local(&end).date_naive())
+                    .unwrap_or_default()
+            })
+            .into_iter()
+            .map(|(day, tasks)| (day, tasks.collect()))
+            .collect();
+        Ok(days)
+    }
+    /// users authorize against the shared database, the one whoever runs the
+    /// server syncs, their own account is set up after
     pub async fn get_authorize_task(&self) -> Result<Task> {
         self.repo
-            .find(&|task| task.get_description().starts_with("taskbane:"))
+            .find(Uuid::nil(), &|task| task.get_description().starts_with("taskbane:"))
             .await
             .and_then(|maybe_task| maybe_task.ok_or(anyhow::anyhow!("No authorizing task found")))
     }
 
-    pub async fn mark_task_done(&self, uuid: Uuid) -> Result<()> {
-        self.repo.mark_task_done(uuid).await
+    /// the task's blockers and the tasks it blocks, transitively
+    pub async fn dependencies(&self, user_id: Uuid, uuid: Uuid) -> Result<TaskDependencies> {
+        let coeffs = self.settings.urgency(user_id).await?;
+        let tz = self.timezone(user_id).await?;
+        let tasks = self.repo.list_all(user_id).await?;
+        let graph = DependencyGraph::new(
+            tasks
+                .iter()
+                .map(|(_, task, _)| (task.get_uuid(), task.get_dependencies().collect())),
+        );
+        let tasks: HashMap<Uuid, TaskDto> = tasks
+            .into_iter()
+            .map(|(id, task, deps)| (task.get_uuid(), TaskDto::from(id, task, deps, &coeffs, tz)))
+            .collect();
+        let lookup = |nodes: Vec<(usize, Uuid)>| {
+            nodes
+                .into_iter()
+                .filter_map(|(depth, uuid)| tasks.get(&uuid).map(|task| (depth, task.clone())))
+                .collect()
+        };
+
+        Ok(TaskDependencies {
+            task: tasks
+                .get(&uuid)
+                .cloned()
+                .ok_or(anyhow!("No task found for uuid"))?,
+            upstream: lookup(graph.upstream(uuid)),
+            downstream: lookup(graph.downstream(uuid)),
+        })
+    }
+
+    /// rejects new deps that would end up waiting on `uuid` itself
+    async fn check_cycles(&self, user_id: Uuid, uuid: Uuid, deps: &[Uuid]) -> Result<()> {
+        let tasks = self.repo.list_all(user_id).await?;
+        let graph = DependencyGraph::new(
+            tasks
+                .iter()
+                .map(|(_, task, _)| (task.get_uuid(), task.get_dependencies().collect())),
+        );
+        // tasks outside the working set go by their short uuid, like on the cards
+        let name = |uuid: &Uuid| match tasks.iter().find(|(_, task, _)| task.get_uuid() == *uuid) {
+            Some((id, _, _)) if *id != 0 => id.to_string(),
+            _ => uuid.to_string()[..8].to_owned(),
+        };
+        let current: Vec<Uuid> = tasks
+            .iter()
+            .find(|(_, task, _)| task.get_uuid() == uuid)
+            .map(|(_, task, _)| task.get_dependencies().collect())
+            .unwrap_or_default();
+
+        for dep in deps.iter().filter(|dep| !current.contains(dep)) {
+            if let Some(cycle) = graph.cycle_with(uuid, *dep) {
+                bail!(
+                    "Depending on {} would create a cycle: {}",
+                    name(dep),
+                    cycle.iter().map(name).join(" → ")
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// applies one action from the bulk bar to every selected task, `value` is
+    /// the tag, priority, due date or project it needs
+    pub async fn bulk(
+        &self,
+        user_id: Uuid,
+        uuids: &[Uuid],
+        action: &str,
+        value: &str,
+    ) -> Result<()> {
+        if uuids.is_empty() {
+            bail!("Select at least one task");
+        }
+        let value = value.trim();
+        let tag = || {
+            Tag::try_from(value.trim_start_matches('+'))
+                .map_err(|_| anyhow!("'{value}' is not a valid tag"))
+        };
+        let action = match action {
+            "done" => BulkAction::Done,
+            "delete" => BulkAction::Delete,
+            "tag" => BulkAction::AddTag(tag()?),
+            "untag" => BulkAction::RemoveTag(tag()?),
+            "priority" => {
+                let priority = value.to_lowercase();
+                if !["", "h", "m", "l"].contains(&priority.as_str()) {
+                    bail!("Priority must be h, m, l or empty");
+                }
+                BulkAction::Priority(priority)
+            }
+            "due" => BulkAction::Due(match value {
+                "" => None,
+                due => Some(from_wall_clock(
+                    self.parse_datetime(user_id, due)
+                        .await
+                        .map_err(|_| anyhow!("Could not parse due '{due}'"))?,
+                    self.timezone(user_id).await?,
+                )),
+            }),
+            "project" => BulkAction::Project(Some(value.to_owned()).filter(|p| !p.is_empty())),
+            _ => bail!("Unknown bulk action '{action}'"),
+        };
+
+        self.repo.bulk_update(user_id, uuids, &[action]).await
+    }
+
+    /// pending tasks in columns by `grouping`, the state board also shows waiting
+    /// tasks and the ones done in the last week
+    pub async fn board(&self, user_id: Uuid, grouping: Grouping) -> Result<Vec<BoardColumn>> {
+        let mut tasks = self.list(user_id, None).await?;
+        if grouping == Grouping::State {
+            let since = Utc::now() - chrono::Duration::days(BOARD_DONE_DAYS);
+            let pending = self.list_with_status(user_id, Status::Pending).await?;
+            let completed = self.list_with_status(user_id, Status::Completed).await?;
+            tasks.extend(pending.into_iter().filter(|task| task.wait.is_some()));
+            tasks.extend(
+                completed
+                    .into_iter()
+                    .filter(|task| task.end_at.is_some_and(|end| end >= since)),
+            );
+        }
+        Ok(grouping.columns(tasks))
+    }
+
+    /// a card dropped from column `from` onto `to`, as the change that puts it there
+    pub async fn move_on_board(
+        &self,
+        user_id: Uuid,
+        uuid: Uuid,
+        grouping: Grouping,
+        from: &str,
+        to: &str,
+    ) -> Result<()> {
+        if from == to {
+            return Ok(());
+        }
+        let tag = |name: &str| {
+            Tag::try_from(name).map_err(|_| anyhow!("'{name}' is not a valid tag"))
+        };
+        let actions = match grouping {
+            Grouping::Priority => {
+                if !["", "h", "m", "l"].contains(&to) {
+                    bail!("Priority must be h, m, l or empty");
+                }
+                vec![BulkAction::Priority(to.to_owned())]
+            }
+            Grouping::Project => {
+                vec![BulkAction::Project(Some(to.to_owned()).filter(|p| !p.is_empty()))]
+            }
+            // the card only leaves the tag column it was dragged out of
+            Grouping::Tag => {
+                let mut actions = vec![];
+                if !from.is_empty() {
+                    actions.push(BulkAction::RemoveTag(tag(from)?));
+                }
+                if !to.is_empty() {
+                    actions.push(BulkAction::AddTag(tag(to)?));
+                }
+                actions
+            }
+            Grouping::State => {
+                let task = self.get_task(user_id, uuid).await?;
+                let current = TaskState::of(&task);
+                let to = to.parse::<TaskState>()?;
+                if current == to {
+                    return Ok(());
+                }
+                let mut actions = match current {
+                    TaskState::Active if to != TaskState::Done => vec![BulkAction::Stop],
+                    TaskState::Waiting => vec![BulkAction::Wait(None)],
+                    TaskState::Done => vec![BulkAction::Reopen],
+                    _ => vec![],
+                };
+                match to {
+                    TaskState::Pending => {}
+                    TaskState::Active => actions.push(BulkAction::Start),
+                    TaskState::Done => actions.push(BulkAction::Done),
+                    TaskState::Waiting => bail!("Set a wait date from the edit page to hide a task"),
+                }
+                actions
+            }
+        };
+
+        self.repo.bulk_update(user_id, &[uuid], &actions).await
+    }
+
+    /// pending tasks on the days around `anchor`, today when there's none, in
+    /// the user's timezone
+    pub async fn calendar(
+        &self,
+        user_id: Uuid,
+        view: CalendarView,
+        anchor: Option<NaiveDate>,
+    ) -> Result<Calendar> {
+        let tz = self.timezone(user_id).await?;
+        let weekstart = self.settings.weekstart(user_id).await?;
+        let today = Utc::now().with_timezone(&tz).date_naive();
+        let tasks = self.list_with_status(user_id, Status::Pending).await?;
+        Ok(Calendar::new(
+            view,
+            anchor.unwrap_or(today),
+            today,
+            weekstart,
+            tasks,
+            |date| date.with_timezone(&tz).naive_local(),
+        ))
+    }
+
+    pub async fn timezone(&self, user_id: Uuid) -> Result<Tz> {
+        self.settings.timezone(user_id).await
+    }
+
+    /// the time on the user's wall clock
+    pub async fn now(&self, user_id: Uuid) -> Result<DateTime<Tz>> {
+        Ok(Utc::now().with_timezone(&self.timezone(user_id).await?))
+    }
+
+    /// the iCalendar feed behind a secret url, `None` when the token was revoked.
+    /// Only tasks with a due or scheduled date are in it, narrowed by `filter`.
+    pub async fn ical_feed(&self, token: Uuid, filter: &str) -> Result<Option<String>> {
+        let Some(user_id) = self.settings.feed_user(token).await? else {
+            return Ok(None);
+        };
+        let filter = match filter.trim() {
+            "" => None,
+            filter => Some(self.parse_filter(user_id, filter).await?),
+        };
+        let tasks: Vec<TaskDto> = self
+            .list(user_id, filter.as_ref())
+            .await?
+            .into_iter()
+            .filter(|task| task.due_at.is_some() || task.scheduled_at.is_some())
+            .collect();
+        Ok(Some(ical::feed(&tasks, Utc::now())))
+    }
+
+    /// every change made to the task, newest first
+    pub async fn history(&self, user_id: Uuid, uuid: Uuid) -> Result<Vec<HistoryEntry>> {
+        let operations = self.repo.task_operations(user_id, uuid).await?;
+        Ok(history::history(&operations, self.timezone(user_id).await?))
+    }
+
+    /// what an undo would reverse right now, empty once the last change has synced
+    pub async fn undo_preview(&self, user_id: Uuid) -> Result<UndoPreview> {
+        let operations = self.repo.undo_operations(user_id).await?;
+        let tz = self.timezone(user_id).await?;
+        let mut tasks: Vec<(Uuid, String, Vec<String>)> = vec![];
+        for (uuid, change) in operations
+            .iter()
+            .filter_map(|operation| history::describe(operation, tz))
+        {
+            match tasks.iter_mut().find(|(task, _, _)| *task == uuid) {
+                Some((_, _, changes)) => changes.push(change),
+                None => {
+                    let label = self
+                        .repo
+                        .get_task(user_id, uuid)
+                        .await?
+                        .map(|task| task.get_description().to_owned())
+                        .filter(|description| !description.is_empty())
+                        .unwrap_or_else(|| uuid.to_string()[..8].to_owned());
+                    tasks.push((uuid, label, vec![change]));
+                }
+            }
+        }
+
+        Ok(UndoPreview {
+            operations,
+            tasks: tasks
+                .into_iter()
+                .map(|(_, label, changes)| (label, changes))
+                .collect(),
+        })
+    }
+
+    /// reverses the operations from a preview, like `task undo` only unsynced ones
+    pub async fn undo(&self, user_id: Uuid, operations: Vec<Operation>) -> Result<()> {
+        if operations.is_empty() {
+            bail!("Nothing to undo");
+        }
+        if !self.repo.undo(user_id, operations).await? {
+            bail!("These changes were synced or changed since, they can't be undone anymore");
+        }
+        Ok(())
+    }
+
+    /// uuids of tasks in the user's replica as they change, so open pages can catch up
+    pub fn changes(&self, user_id: Uuid) -> broadcast::Receiver<Vec<Uuid>> {
+        self.repo.subscribe(user_id)
+    }
+
+    /// the project hierarchy with pending and completed counts
+    pub async fn projects(&self, user_id: Uuid) -> Result<Vec<ProjectSummary>> {
+        let tasks = self.repo.list_all(user_id).await?;
+        Ok(ProjectSummary::tree(tasks.iter().filter_map(|(_, task, _)| {
+            task.get_user_defined_attribute("project")
+                .map(|project| (project, task.get_status()))
+        })))
+    }
+
+    pub async fn mark_task_done(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        self.repo.mark_task_done(user_id, uuid).await
+    }
+
+    pub async fn start_task(&self, user_id: Uuid, uuid: Uuid) -> Result<TaskDto> {
+        self.repo.start_task(user_id, uuid).await?;
+        self.get_task(user_id, uuid).await
+    }
+
+    pub async fn stop_task(&self, user_id: Uuid, uuid: Uuid) -> Result<TaskDto> {
+        self.repo.stop_task(user_id, uuid).await?;
+        self.get_task(user_id, uuid).await
+    }
+
+    pub async fn delete_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        self.repo.delete_task(user_id, uuid).await
+    }
+
+    pub async fn restore_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        self.repo.restore_task(user_id, uuid).await
+    }
+
+    pub async fn reopen_task(&self, user_id: Uuid, uuid: Uuid) -> Result<()> {
+        self.repo.reopen_task(user_id, uuid).await
+    }
+
+    pub async fn parse_filter(&self, user_id: Uuid, filter: &str) -> Result<Filter> {
+        Filter::parse(filter, self.now(user_id).await?)
+    }
+
+    /// a wall clock time in the user's timezone, relative dates count from their now
+    pub async fn parse_datetime(&self, user_id: Uuid, due: &str) -> Result<NaiveDateTime> {
+        parse_date(due, self.now(user_id).await?.naive_local())
+            .ok_or_else(|| anyhow!("Could not parse"))
+    }
+
+    /// the user's uda schema, what the task forms render inputs for
+    pub async fn udas(&self, user_id: Uuid) -> Result<Vec<Uda>> {
+        self.settings.udas(user_id).await
     }
 
-    pub fn parse_datetime(&self, due: &str) -> Result<NaiveDateTime> {
-        parse_date(due, Local::now().naive_local()).ok_or_else(|| anyhow!("Could not parse"))
+    /// checks form uda values against the schema and stores them the way taskwarrior does
+    async fn normalize_udas(&self, user_id: Uuid, input: &mut CreateTaskInput) -> Result<()> {
+        let schema = self.settings.udas(user_id).await?;
+        let now = self.now(user_id).await?;
+        for (name, value) in input.udas.iter_mut() {
+            let uda = schema
+                .iter()
+                .find(|uda| &uda.name == name)
+                .ok_or_else(|| anyhow!("Unknown attribute '{name}'"))?;
+            if let Some(raw) = value.take() {
+                *value = uda.normalize(&raw, now)?;
+            }
+        }
+        Ok(())
     }
 
-    pub async fn create_task(&self, input: CreateTaskInput) -> Result<usize> {
-        self.repo.create_task(input).await
+    pub async fn create_task(&self, user_id: Uuid, mut input: CreateTaskInput) -> Result<usize> {
+        self.normalize_udas(user_id, &mut input).await?;
+        let recurring = input.recur.is_some();
+        if let Some(recur) = &input.recur {
+            recur.parse::<Period>()?;
+            if input.due.is_none() {
+                bail!("A recurring task needs a due date");
+            }
+        }
+
+        let id = self.repo.create_task(user_id, input).await?;
+        // don't make the user wait for the loop to see the first instance
+        if recurring {
+            self.generate_recurring_for(user_id).await?;
+        }
+
+        Ok(id)
     }
 
-    pub async fn annotate_task(&self, uuid: Uuid, description: &str) -> Result<Annotation> {
+    pub async fn update_task(
+        &self,
+        user_id: Uuid,
+        uuid: Uuid,
+        mut input: CreateTaskInput,
+    ) -> Result<()> {
+        self.normalize_udas(user_id, &mut input).await?;
+        let task = self
+            .repo
+            .get_task(user_id, uuid)
+            .await?
+            .ok_or(anyhow!("No task found"))?;
+        if task.get_status() == Status::Recurring {
+            let recur = input
+                .recur
+                .as_ref()
+                .ok_or(anyhow!("A recurring task needs a recurrence"))?;
+            recur.parse::<Period>()?;
+            if input.due.is_none() {
+                bail!("A recurring task needs a due date");
+            }
+        } else if input.recur.is_some() {
+            bail!("Only new tasks can be made recurring");
+        }
+        self.check_cycles(user_id, uuid, &input.deps).await?;
+
+        self.repo.update_task(user_id, uuid, input).await
+    }
+
+    /// create the missing children of every recurring template in every replica,
+    /// returns how many were made. A replica that fails is logged and skipped,
+    /// the others still get theirs
+    pub async fn generate_recurring(&self) -> Result<usize> {
+        let mut created = 0;
+        for owner in self.repo.owners() {
+            match self.generate_recurring_for(owner).await {
+                Ok(count) => created += count,
+                Err(err) => info!("recurrence err for {owner}: {err:?}"),
+            }
+        }
+        Ok(created)
+    }
+
+    /// the missing children of the templates in `user_id`'s replica. Like taskwarrior,
+    /// a replica that hasn't synced another one's children yet makes its own.
+    async fn generate_recurring_for(&self, user_id: Uuid) -> Result<usize> {
+        let now = Utc::now();
+        let mut created = 0;
+
+        for (_, template, _) in self.repo.list_with_status(user_id, Status::Recurring).await? {
+            created += self
+                .repo
+                .create_recurrences(user_id, template.get_uuid(), now)
+                .await?;
+        }
+
+        Ok(created)
+    }
+
+    pub async fn annotate_task(
+        &self,
+        user_id: Uuid,
+        uuid: Uuid,
+        description: &str,
+    ) -> Result<Annotation> {
         let annotation = Annotation {
-            entry: Local::now().to_utc(),
+            entry: Utc::now(),
             description: description.to_owned(),
         };
 
-        self.repo.annotate(uuid, annotation.clone()).await?;
+        self.repo.annotate(user_id, uuid, annotation.clone()).await?;
 
         Ok(annotation)
     }
 }
 
-impl From<CreateTaskQuery> for CreateTaskInput {
-    fn from(value: CreateTaskQuery) -> Self {
-        let now = Local::now().naive_local();
-        let due = value
-            .due
-            .and_then(move |due| parse_date(&due, now))
-            .map(|date| date.and_utc());
+/// form dates are wall clock times in the timezone of `now`, the user's
+impl TryFrom<(CreateTaskQuery, DateTime<Tz>)> for CreateTaskInput {
+    type Error = ApiError;
+
+    fn try_from((value, now): (CreateTaskQuery, DateTime<Tz>)) -> Result<Self, Self::Error> {
+        let date = |name: &str, input: Option<String>| {
+            let Some(input) = input.filter(|input| !input.trim().is_empty()) else {
+                return Ok(None);
+            };
+            parse_date(&input, now.naive_local())
+                .map(|date| Some(from_wall_clock(date, now.timezone())))
+                .ok_or_else(|| ApiError::BadRequest {
+                    message: format!("Could not parse {name} date '{input}'"),
+                })
+        };
+        let recur = Some(value.recur.trim().to_owned()).filter(|recur| !recur.is_empty());
 
         let tags = value
             .tags
+            .iter()
+            .map(|tag| {
+                Tag::try_from(tag).map_err(|err| ApiError::BadRequest {
+                    message: format!("Invalid tag '{tag}': {err}"),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let project = Some(value.project.trim().to_owned()).filter(|project| !project.is_empty());
+
+        let udas = value
+            .uda_names
             .into_iter()
-            .map(|tag| Tag::try_from(&tag))
-            .collect::<Result<Vec<_>>>()
-            .inspect_err(|err| info!("Error converting tags: {err:?}"))
-            .unwrap_or_default();
+            .zip(value.uda_values)
+            .map(|(name, value)| (name, Some(value).filter(|value| !value.trim().is_empty())))
+            .collect();
 
-        Self::new(value.description, value.priority, value.deps, tags, due)
+        Ok(Self {
+            description: value.description,
+            priority: value.priority,
+            project,
+            deps: value.deps,
+            tags,
+            due: date("due", value.due)?,
+            wait: date("wait", value.wait)?,
+            scheduled: date("scheduled", value.scheduled)?,
+            recur,
+            until: date("until", value.until)?,
+            udas,
+        })
     }
 }
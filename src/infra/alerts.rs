@@ -31,10 +31,28 @@ impl Display for AlertLevel {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Constructor)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alert {
     pub level: AlertLevel,
     pub message: String,
+    /// offers to undo the change the alert is about
+    #[serde(default)]
+    pub undo: bool,
+}
+
+impl Alert {
+    pub fn new(level: AlertLevel, message: String) -> Self {
+        Self {
+            level,
+            message,
+            undo: false,
+        }
+    }
+
+    pub fn undoable(mut self) -> Self {
+        self.undo = true;
+        self
+    }
 }
 
 pub type Alerts = Vec<Alert>;
@@ -44,10 +62,11 @@ pub type Alerts = Vec<Alert>;
 pub struct AlertTempl {
     level: AlertLevel,
     message: String,
+    undo: bool,
 }
 
 pub fn map_err_to_alert<E: std::fmt::Display>(err: E) -> Response {
-    let alert = AlertTempl::new(AlertLevel::Error, err.to_string());
+    let alert = AlertTempl::new(AlertLevel::Error, err.to_string(), false);
 
     match alert.render() {
         Ok(html) => html.into_response(),
@@ -0,0 +1,98 @@
+use std::{env, num::NonZeroU32};
+
+use anyhow::{anyhow, Result};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+
+/// rounds of PBKDF2 the passphrase goes through, the key is derived once per process
+const PBKDF2_ROUNDS: NonZeroU32 = NonZeroU32::new(100_000).unwrap();
+
+/// Seals secrets kept in the database with a key that isn't
+pub struct Cipher {
+    key: LessSafeKey,
+    rng: SystemRandom,
+}
+
+impl Cipher {
+    /// any passphrase works, it's stretched into the key with `salt`
+    pub fn new(passphrase: &str, salt: &[u8]) -> Self {
+        let mut key = [0u8; 32];
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            PBKDF2_ROUNDS,
+            salt,
+            passphrase.as_bytes(),
+            &mut key,
+        );
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &key).expect("32 bytes is a chacha20 key");
+        Self {
+            key: LessSafeKey::new(key),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    /// `TASK_CREDENTIALS_KEY`, `None` when unset
+    pub fn passphrase_from_env() -> Option<String> {
+        env::var("TASK_CREDENTIALS_KEY")
+            .ok()
+            .filter(|passphrase| !passphrase.is_empty())
+    }
+
+    /// a fresh nonce and `plain` sealed with it. `aad` isn't sealed but has to be
+    /// the same to open it again, so a secret can't be moved to another owner
+    pub fn seal(&self, plain: &[u8], aad: &[u8]) -> Result<(Vec<u8>, Vec<u8>)> {
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut nonce)
+            .map_err(|_| anyhow!("no randomness for a nonce"))?;
+        let mut sealed = plain.to_vec();
+        self.key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad),
+                &mut sealed,
+            )
+            .map_err(|_| anyhow!("could not seal secret"))?;
+        Ok((nonce.to_vec(), sealed))
+    }
+
+    pub fn open(&self, nonce: &[u8], sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("bad nonce"))?;
+        let mut sealed = sealed.to_vec();
+        let plain = self
+            .key
+            .open_in_place(nonce, Aad::from(aad), &mut sealed)
+            .map_err(|_| anyhow!("secret doesn't open with this key, was it changed?"))?;
+        Ok(plain.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seals_and_opens() {
+        let cipher = Cipher::new("correct horse", b"salt");
+        let (nonce, sealed) = cipher.seal(b"hunter2", b"me").unwrap();
+        assert_ne!(sealed, b"hunter2");
+        assert_eq!(cipher.open(&nonce, &sealed, b"me").unwrap(), b"hunter2");
+
+        // a second seal of the same secret doesn't look the same
+        let (other_nonce, other) = cipher.seal(b"hunter2", b"me").unwrap();
+        assert_ne!((&nonce, &sealed), (&other_nonce, &other));
+
+        assert!(Cipher::new("battery staple", b"salt").open(&nonce, &sealed, b"me").is_err());
+        assert!(Cipher::new("correct horse", b"pepper").open(&nonce, &sealed, b"me").is_err());
+    }
+
+    #[test]
+    fn sealed_for_one_owner_only() {
+        let cipher = Cipher::new("correct horse", b"salt");
+        let (nonce, sealed) = cipher.seal(b"hunter2", b"alice").unwrap();
+        assert!(cipher.open(&nonce, &sealed, b"mallory").is_err());
+    }
+}
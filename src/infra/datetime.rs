@@ -1,4 +1,5 @@
-use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Weekday};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
 
 /// WARNING: mostly slopcoded, needs further testing
 /// Parse a Taskwarrior-compatible date string relative to `now`.
@@ -332,7 +333,7 @@ fn week_extra(d: NaiveDate) -> i64 {
     (wday(d) + 6) % 7
 }
 
-fn month_start(d: NaiveDate, offset: i32) -> NaiveDate {
+pub fn month_start(d: NaiveDate, offset: i32) -> NaiveDate {
     let mut year = d.year();
     let mut month = d.month() as i32 + offset;
     while month > 12 {
@@ -346,12 +347,27 @@ fn month_start(d: NaiveDate, offset: i32) -> NaiveDate {
     NaiveDate::from_ymd_opt(year, month as u32, 1).unwrap()
 }
 
-fn month_end(d: NaiveDate, offset: i32) -> NaiveDate {
+pub fn month_end(d: NaiveDate, offset: i32) -> NaiveDate {
     // Start of the month *after* the target, minus one day
     let start_of_next = month_start(d, offset + 1);
     start_of_next - Duration::days(1)
 }
 
+/// The day a week containing `d` starts on, for calendars that start on `weekstart`.
+pub fn week_start(d: NaiveDate, weekstart: Weekday) -> NaiveDate {
+    let back = (d.weekday().num_days_from_monday() + 7 - weekstart.num_days_from_monday()) % 7;
+    d - Duration::days(back as i64)
+}
+
+/// A wall clock time in `tz` as the instant it names. In a DST gap the hour
+/// that doesn't exist is taken as UTC rather than failing.
+pub fn from_wall_clock(date: NaiveDateTime, tz: Tz) -> DateTime<Utc> {
+    tz.from_local_datetime(&date)
+        .earliest()
+        .map(|date| date.to_utc())
+        .unwrap_or_else(|| date.and_utc())
+}
+
 fn quarter_of(month: u32) -> u32 {
     (month - 1) / 3 // 0-based quarter index
 }
@@ -937,4 +953,33 @@ mod tests {
     fn case_march_upper() {
         assert_eq!(p("MARCH"), Some(date(2025, 3, 1)));
     }
+
+    // ── calendar helpers ───────────────────────────────────────────────────────
+
+    #[test]
+    fn week_start_monday_and_sunday() {
+        let tue = date(2024, 3, 5).date();
+        assert_eq!(week_start(tue, Weekday::Mon), date(2024, 3, 4).date());
+        assert_eq!(week_start(tue, Weekday::Sun), date(2024, 3, 3).date());
+        let sun = date(2024, 3, 10).date();
+        assert_eq!(week_start(sun, Weekday::Sun), sun);
+    }
+
+    #[test]
+    fn wall_clock_in_timezone() {
+        let tz: Tz = "Europe/Berlin".parse().unwrap();
+        assert_eq!(
+            from_wall_clock(dt(2024, 3, 5, 9, 0, 0), tz),
+            dt(2024, 3, 5, 8, 0, 0).and_utc()
+        );
+        // 02:30 doesn't exist on the day clocks go forward
+        assert_eq!(
+            from_wall_clock(dt(2024, 3, 31, 2, 30, 0), tz),
+            dt(2024, 3, 31, 2, 30, 0).and_utc()
+        );
+        assert_eq!(
+            from_wall_clock(dt(2024, 3, 5, 9, 0, 0), Tz::UTC),
+            dt(2024, 3, 5, 9, 0, 0).and_utc()
+        );
+    }
 }
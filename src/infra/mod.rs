@@ -2,10 +2,13 @@ pub mod alerts;
 pub mod askama;
 pub mod auth;
 pub mod axum;
+pub mod crypto;
 pub mod datetime;
 pub mod error;
 pub mod livereload;
+pub mod replicas;
 pub mod sqlx;
+pub mod sync_server;
 pub mod task;
 pub mod tower_session;
 pub mod webauthn;
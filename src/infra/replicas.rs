@@ -0,0 +1,227 @@
+use std::{
+    collections::HashMap,
+    env,
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use anyhow::{Context, Result};
+use sqlx::{sqlite::SqliteConnectOptions, SqlitePool};
+use taskchampion::Replica;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+use uuid::Uuid;
+
+use super::{
+    sync_server::SyncBackend,
+    task::{ArcRep, SqlxStorage, SyncMonitor},
+};
+
+/// A replica with the database it lives in and what syncs it
+#[derive(Clone)]
+pub struct Tenant {
+    pub replica: ArcRep<SqlxStorage>,
+    /// the replica's database, for reading sync state and the search index
+    pub pool: SqlitePool,
+    pub monitor: Arc<SyncMonitor>,
+    pub backend: SyncBackend,
+    /// stops the tenant's sync, cancelled on shutdown too
+    pub stop: CancellationToken,
+}
+
+impl Tenant {
+    fn new(pool: SqlitePool, backend: SyncBackend, stop: CancellationToken) -> Self {
+        Self {
+            replica: Arc::new(tokio::sync::RwLock::new(Replica::new(SqlxStorage::new(
+                pool.clone(),
+            )))),
+            pool,
+            monitor: Arc::new(SyncMonitor::default()),
+            backend,
+            stop,
+        }
+    }
+}
+
+/// Every replica on this server. Users with their own taskchampion account get a
+/// database of their own next to the main one, everyone else shares the replica
+/// in the main database.
+pub struct Replicas {
+    shared: Tenant,
+    /// where the databases of users with their own account go
+    data_dir: PathBuf,
+    own: RwLock<HashMap<Uuid, Tenant>>,
+    shutdown: CancellationToken,
+    /// replicas the sync loop hasn't picked up yet
+    added: mpsc::UnboundedSender<Tenant>,
+    added_rx: Mutex<Option<mpsc::UnboundedReceiver<Tenant>>>,
+}
+
+impl Replicas {
+    /// the shared replica syncs with the backend from the environment, own
+    /// databases go in `TASK_DATA_DIR`, `taskdb` by default
+    pub fn new(pool: &SqlitePool, shutdown: CancellationToken) -> Result<Arc<Self>> {
+        let backend = SyncBackend::from_env(|name| env::var(name).ok())?;
+        info!("task sync backend: {backend}");
+        let data_dir = env::var("TASK_DATA_DIR").unwrap_or_else(|_| "taskdb".to_owned());
+        Ok(Self::with_backend(pool, backend, data_dir.into(), shutdown))
+    }
+
+    /// the shared replica syncs with `backend`, own databases go in `data_dir`
+    pub(crate) fn with_backend(
+        pool: &SqlitePool,
+        backend: SyncBackend,
+        data_dir: PathBuf,
+        shutdown: CancellationToken,
+    ) -> Arc<Self> {
+        let (added, added_rx) = mpsc::unbounded_channel();
+        let shared = Tenant::new(pool.clone(), backend, shutdown.child_token());
+        let _ = added.send(shared.clone());
+
+        Arc::new(Self {
+            shared,
+            data_dir,
+            own: RwLock::new(HashMap::new()),
+            shutdown,
+            added,
+            added_rx: Mutex::new(Some(added_rx)),
+        })
+    }
+
+    /// the replica `user_id` works on
+    pub fn get(&self, user_id: Uuid) -> Tenant {
+        self.own
+            .read()
+            .unwrap()
+            .get(&user_id)
+            .cloned()
+            .unwrap_or_else(|| self.shared.clone())
+    }
+
+    /// a user for every replica, the nil uuid stands for the shared one
+    pub fn owners(&self) -> Vec<Uuid> {
+        let own = self.own.read().unwrap();
+        std::iter::once(Uuid::nil())
+            .chain(own.keys().copied())
+            .collect()
+    }
+
+    /// opens `user_id`'s own database and syncs it with `backend` from now on,
+    /// replacing the one they had open
+    pub async fn open(&self, user_id: Uuid, backend: SyncBackend) -> Result<()> {
+        std::fs::create_dir_all(&self.data_dir)
+            .with_context(|| format!("creating {}", self.data_dir.display()))?;
+        let options = SqliteConnectOptions::new()
+            .filename(self.data_dir.join(format!("{user_id}.sqlite3")))
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        sqlx::migrate!("./migrations/taskdb").run(&pool).await?;
+
+        let tenant = Tenant::new(pool, backend, self.shutdown.child_token());
+        if let Some(old) = self.own.write().unwrap().insert(user_id, tenant.clone()) {
+            old.stop.cancel();
+        }
+        let _ = self.added.send(tenant);
+        Ok(())
+    }
+
+    /// back to the shared replica, the user's database stays on disk
+    pub fn close(&self, user_id: Uuid) {
+        if let Some(tenant) = self.own.write().unwrap().remove(&user_id) {
+            tenant.stop.cancel();
+        }
+    }
+
+    /// replicas opened so far and from now on, for the one sync loop
+    pub fn take_added(&self) -> Option<mpsc::UnboundedReceiver<Tenant>> {
+        self.added_rx.lock().unwrap().take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn users_with_an_account_get_their_own_replica() {
+        let data_dir = env::temp_dir().join(format!("taskbane-replicas-{}", Uuid::new_v4()));
+        // one connection, every connection to :memory: is a database of its own
+        let shared = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations/taskdb").run(&shared).await.unwrap();
+        let shutdown = CancellationToken::new();
+        let replicas =
+            Replicas::with_backend(&shared, SyncBackend::None, data_dir.clone(), shutdown.clone());
+        let mut added = replicas.take_added().unwrap();
+        assert!(replicas.take_added().is_none());
+        added.recv().await.unwrap();
+
+        let user = Uuid::new_v4();
+        assert_eq!(replicas.owners(), vec![Uuid::nil()]);
+        replicas.open(user, SyncBackend::None).await.unwrap();
+        assert_eq!(replicas.owners(), vec![Uuid::nil(), user]);
+        let own = added.recv().await.unwrap();
+
+        let uuid = Uuid::new_v4();
+        let mut ops = taskchampion::Operations::new();
+        let mut rep = own.replica.write().await;
+        rep.create_task(uuid, &mut ops).await.unwrap();
+        rep.commit_operations(ops).await.unwrap();
+        drop(rep);
+        let mine = replicas.get(user).replica.write().await.get_task(uuid).await;
+        assert!(mine.unwrap().is_some());
+        let shared = replicas.get(Uuid::nil()).replica.write().await.get_task(uuid).await;
+        assert!(shared.unwrap().is_none());
+
+        // everyone else stays on the shared replica
+        assert!(!replicas.get(Uuid::new_v4()).stop.is_cancelled());
+        assert!(Arc::ptr_eq(
+            &replicas.get(Uuid::new_v4()).replica,
+            &replicas.get(Uuid::nil()).replica
+        ));
+
+        replicas.close(user);
+        assert!(own.stop.is_cancelled());
+        assert_eq!(replicas.owners(), vec![Uuid::nil()]);
+
+        std::fs::remove_dir_all(data_dir).unwrap();
+    }
+
+    /// the taskdb_* objects and their columns, however the database got them
+    async fn taskdb_schema(pool: &SqlitePool) -> Vec<(String, String, String)> {
+        sqlx::query_as(
+            r#"
+                SELECT m.type, m.name, coalesce(group_concat(p.name || ' ' || p.type, ', '), '')
+                FROM sqlite_master m
+                LEFT JOIN pragma_table_info(m.name) p
+                WHERE m.name LIKE 'taskdb\_%' ESCAPE '\'
+                GROUP BY m.type, m.name
+                ORDER BY m.type, m.name
+            "#,
+        )
+        .fetch_all(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn own_databases_match_the_main_one() {
+        let memory = || {
+            sqlx::sqlite::SqlitePoolOptions::new()
+                .max_connections(1)
+                .connect("sqlite::memory:")
+        };
+        let main = memory().await.unwrap();
+        sqlx::migrate!().run(&main).await.unwrap();
+        let own = memory().await.unwrap();
+        sqlx::migrate!("./migrations/taskdb").run(&own).await.unwrap();
+
+        let schema = taskdb_schema(&main).await;
+        assert!(schema.iter().any(|(_, name, _)| name == "taskdb_search_rows"));
+        assert_eq!(schema, taskdb_schema(&own).await);
+    }
+}
@@ -0,0 +1,274 @@
+use std::{fmt, path::PathBuf};
+
+use anyhow::{anyhow, bail, Context, Error, Result};
+use async_trait::async_trait;
+use sqlx::{query, sqlite::SqliteConnectOptions, Row, SqlitePool};
+use taskchampion::{
+    server::{
+        AddVersionResult, GetVersionResult, HistorySegment, Snapshot, SnapshotUrgency, VersionId,
+        NIL_VERSION_ID,
+    },
+    Server, ServerConfig,
+};
+use uuid::Uuid;
+
+type TcResult<T> = std::result::Result<T, taskchampion::Error>;
+
+/// the file taskchampion's own local server keeps in its directory, so the
+/// task cli and taskbane can sync through the same one
+const LOCAL_DB_FILE: &str = "taskchampion-local-sync-server.sqlite3";
+
+/// Where the replica syncs to
+#[derive(Debug, Clone)]
+pub enum SyncBackend {
+    /// a local-only replica, nothing to sync with
+    None,
+    Remote {
+        url: String,
+        client_id: Uuid,
+        encryption_secret: Vec<u8>,
+    },
+    /// a directory shared with other replicas
+    Local { directory: PathBuf },
+}
+
+impl SyncBackend {
+    /// from `TASK_SYNC`, `none`, `remote` or `local`. Without it a set `TASK_URL`
+    /// means remote, as it did before there was a choice, and none otherwise
+    pub fn from_env(env: impl Fn(&str) -> Option<String>) -> Result<Self> {
+        let kind = match env("TASK_SYNC") {
+            Some(kind) => kind,
+            None if env("TASK_URL").is_some() => "remote".to_owned(),
+            None => "none".to_owned(),
+        };
+        let required =
+            |name: &str| env(name).ok_or_else(|| anyhow!("{name} is required for {kind} sync"));
+
+        match kind.as_str() {
+            "none" => Ok(SyncBackend::None),
+            "remote" => Ok(SyncBackend::Remote {
+                url: required("TASK_URL")?,
+                client_id: Uuid::parse_str(&required("TASK_CLIENT_ID")?)
+                    .context("TASK_CLIENT_ID is not a uuid")?,
+                encryption_secret: required("TASK_SECRET")?.into(),
+            }),
+            "local" => Ok(SyncBackend::Local {
+                directory: required("TASK_SYNC_DIR")?.into(),
+            }),
+            _ => bail!("Unknown sync backend '{kind}', expected none, remote or local"),
+        }
+    }
+
+    /// the server to sync with, `None` for a local-only replica
+    pub async fn into_server(self) -> Result<Option<Box<dyn Server>>> {
+        match self {
+            SyncBackend::None => Ok(None),
+            SyncBackend::Remote {
+                url,
+                client_id,
+                encryption_secret,
+            } => {
+                let config = ServerConfig::Remote {
+                    url,
+                    client_id,
+                    encryption_secret,
+                };
+                Ok(Some(config.into_server().await?))
+            }
+            SyncBackend::Local { directory } => {
+                Ok(Some(Box::new(LocalServer::new(directory).await?)))
+            }
+        }
+    }
+}
+
+impl fmt::Display for SyncBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyncBackend::None => write!(f, "none"),
+            SyncBackend::Remote { url, .. } => write!(f, "remote {url}"),
+            SyncBackend::Local { directory } => write!(f, "local {}", directory.display()),
+        }
+    }
+}
+
+/// taskchampion's local-directory server on sqlx. Its own needs rusqlite,
+/// which can't link next to the sqlite sqlx brings in
+pub struct LocalServer {
+    pool: SqlitePool,
+}
+
+impl LocalServer {
+    pub async fn new(directory: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&directory)
+            .with_context(|| format!("creating sync directory {}", directory.display()))?;
+        let options = SqliteConnectOptions::new()
+            .filename(directory.join(LOCAL_DB_FILE))
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(options).await?;
+        query("CREATE TABLE IF NOT EXISTS data (key STRING PRIMARY KEY, value STRING)")
+            .execute(&pool)
+            .await?;
+        query(
+            "CREATE TABLE IF NOT EXISTS versions \
+             (version_id STRING PRIMARY KEY, parent_version_id STRING, data STRING)",
+        )
+        .execute(&pool)
+        .await?;
+
+        Ok(Self { pool })
+    }
+}
+
+fn server_err(err: impl Into<Error>) -> taskchampion::Error {
+    taskchampion::Error::Other(err.into())
+}
+
+#[async_trait(?Send)]
+impl Server for LocalServer {
+    async fn add_version(
+        &mut self,
+        parent_version_id: VersionId,
+        history_segment: HistorySegment,
+    ) -> TcResult<(AddVersionResult, SnapshotUrgency)> {
+        // check and move the latest version together, another replica may be syncing
+        let mut tx = self.pool.begin().await.map_err(server_err)?;
+        let latest = query("SELECT value FROM data WHERE key = 'latest_version_id'")
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(server_err)?
+            .map(|row| Uuid::parse_str(row.get("value")))
+            .transpose()
+            .map_err(server_err)?
+            .unwrap_or(NIL_VERSION_ID);
+        if latest != NIL_VERSION_ID && parent_version_id != latest {
+            return Ok((
+                AddVersionResult::ExpectedParentVersion(latest),
+                SnapshotUrgency::None,
+            ));
+        }
+
+        let version_id = Uuid::new_v4();
+        query("INSERT INTO versions (version_id, parent_version_id, data) VALUES (?, ?, ?)")
+            .bind(version_id.to_string())
+            .bind(parent_version_id.to_string())
+            .bind(history_segment)
+            .execute(&mut *tx)
+            .await
+            .map_err(server_err)?;
+        query("INSERT OR REPLACE INTO data (key, value) VALUES ('latest_version_id', ?)")
+            .bind(version_id.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(server_err)?;
+        tx.commit().await.map_err(server_err)?;
+
+        Ok((AddVersionResult::Ok(version_id), SnapshotUrgency::None))
+    }
+
+    async fn get_child_version(
+        &mut self,
+        parent_version_id: VersionId,
+    ) -> TcResult<GetVersionResult> {
+        let row = query("SELECT version_id, data FROM versions WHERE parent_version_id = ?")
+            .bind(parent_version_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(server_err)?;
+        let Some(row) = row else {
+            return Ok(GetVersionResult::NoSuchVersion);
+        };
+
+        Ok(GetVersionResult::Version {
+            version_id: Uuid::parse_str(row.get("version_id")).map_err(server_err)?,
+            parent_version_id,
+            history_segment: row.get("data"),
+        })
+    }
+
+    async fn add_snapshot(&mut self, _version_id: VersionId, _snapshot: Snapshot) -> TcResult<()> {
+        // never asked for, `add_version` always answers with no urgency
+        Ok(())
+    }
+
+    async fn get_snapshot(&mut self) -> TcResult<Option<(VersionId, Snapshot)>> {
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use taskchampion::{storage::inmemory::InMemoryStorage, Replica, Status};
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn backend_from_env() {
+        assert!(matches!(
+            SyncBackend::from_env(env(&[])).unwrap(),
+            SyncBackend::None
+        ));
+
+        let remote = env(&[
+            ("TASK_URL", "https://tasks.example.com"),
+            ("TASK_CLIENT_ID", "c3d0a5b1-2f5e-4a53-9a3b-f0e8f4a1b2c3"),
+            ("TASK_SECRET", "secret"),
+        ]);
+        assert!(matches!(
+            SyncBackend::from_env(remote).unwrap(),
+            SyncBackend::Remote { .. }
+        ));
+
+        let local = env(&[("TASK_SYNC", "local"), ("TASK_SYNC_DIR", "/srv/tasks")]);
+        assert_eq!(
+            SyncBackend::from_env(local).unwrap().to_string(),
+            "local /srv/tasks"
+        );
+
+        assert!(SyncBackend::from_env(env(&[("TASK_SYNC", "local")])).is_err());
+        assert!(SyncBackend::from_env(env(&[("TASK_URL", "https://tasks.example.com")])).is_err());
+        assert!(SyncBackend::from_env(env(&[("TASK_SYNC", "cloud")])).is_err());
+    }
+
+    #[tokio::test]
+    async fn replicas_sync_through_a_directory() {
+        let directory = std::env::temp_dir().join(format!("taskbane-sync-{}", Uuid::new_v4()));
+        let backend = SyncBackend::Local {
+            directory: directory.clone(),
+        };
+        let mut first_server = backend.clone().into_server().await.unwrap().unwrap();
+        let mut second_server = backend.into_server().await.unwrap().unwrap();
+
+        let mut first = Replica::new(InMemoryStorage::new());
+        let mut ops = taskchampion::Operations::new();
+        let mut task = first.create_task(Uuid::new_v4(), &mut ops).await.unwrap();
+        task.set_description("shared".into(), &mut ops).unwrap();
+        first.commit_operations(ops).await.unwrap();
+        first.sync(&mut first_server, false).await.unwrap();
+
+        let mut second = Replica::new(InMemoryStorage::new());
+        second.sync(&mut second_server, false).await.unwrap();
+        let synced = second.get_task(task.get_uuid()).await.unwrap().unwrap();
+        assert_eq!(synced.get_description(), "shared");
+
+        // the second replica's change goes back the other way
+        let mut ops = taskchampion::Operations::new();
+        let mut synced = synced;
+        synced.done(&mut ops).unwrap();
+        second.commit_operations(ops).await.unwrap();
+        second.sync(&mut second_server, false).await.unwrap();
+        first.sync(&mut first_server, false).await.unwrap();
+        let task = first.get_task(task.get_uuid()).await.unwrap().unwrap();
+        assert_eq!(task.get_status(), Status::Completed);
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
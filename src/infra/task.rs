@@ -1,18 +1,21 @@
-use std::{env, fmt::Display, sync::Arc};
+use std::{collections::HashMap, fmt::Display, sync::Arc, time::Duration};
 
-use anyhow::{Error, Result};
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use derive_more::Constructor;
+use itertools::Itertools;
 use sqlx::{query, Sqlite, SqlitePool, Transaction};
 use taskchampion::{
     server::VersionId,
     storage::{Storage, StorageTxn, TaskMap},
-    Error as TcError, Operation, Replica, ServerConfig,
+    Error as TcError, Operation, Replica, TaskData,
 };
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, watch, Notify};
+use tokio_util::sync::CancellationToken;
 use tracing::info;
 use uuid::Uuid;
 
+use super::replicas::{Replicas, Tenant};
 use crate::types::ArcRw;
 
 // TODO: use mutex instead
@@ -21,60 +24,263 @@ use crate::types::ArcRw;
 // making rwlock pointless over mutex
 pub type ArcRep<S> = ArcRw<Replica<S>>;
 
-pub async fn create_task_storage(conn: &SqlitePool) -> Result<(ArcRep<SqlxStorage>, ServerConfig)> {
-    let storage = SqlxStorage::new(conn.clone());
-    let url = env::var("TASK_URL").expect("No taskserver url provided");
+/// how long an idle loop waits before pulling changes made on other replicas
+const PULL_INTERVAL: Duration = Duration::from_secs(30);
+/// local changes in quick succession go out in one sync
+const DEBOUNCE: Duration = Duration::from_secs(2);
+/// a steady stream of changes still goes out this long after the first one
+const MAX_DEBOUNCE: Duration = Duration::from_secs(10);
+/// first retry after a failed sync, doubling up to `MAX_BACKOFF`
+const BASE_BACKOFF: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(10 * 60);
+
+/// what the sync loop last did, kept in memory since it only matters while running
+#[derive(Debug, Clone, Default)]
+pub struct SyncRecord {
+    pub last_attempt: Option<DateTime<Utc>>,
+    pub last_success: Option<DateTime<Utc>>,
+    /// the error of the last attempt, cleared once one succeeds
+    pub last_error: Option<String>,
+    pub syncing: bool,
+    /// when a failed sync is retried, `None` when not backing off
+    pub retry_at: Option<DateTime<Utc>>,
+    /// no backend configured, the loop never syncs
+    pub local_only: bool,
+}
 
-    let client_id = env::var("TASK_CLIENT_ID")
-        .map_err(Error::from)
-        .and_then(|id| Uuid::parse_str(&id).map_err(Error::from))
-        .expect("No task client id provided");
+/// how many change events a slow listener can fall behind before it misses some
+const CHANGES_CAPACITY: usize = 64;
+
+/// shared between the sync loop and whoever wants to watch or wake it
+#[derive(Debug)]
+pub struct SyncMonitor {
+    record: watch::Sender<SyncRecord>,
+    /// a manual sync, skips debounce and backoff
+    wake: Notify,
+    /// something was committed locally
+    changed: Notify,
+    /// uuids of tasks changed by a local commit or a sync
+    changes: broadcast::Sender<Vec<Uuid>>,
+}
 
-    let encryption_secret: Vec<u8> = env::var("TASK_SECRET")
-        .expect("No task secret provided")
-        .into();
+impl Default for SyncMonitor {
+    fn default() -> Self {
+        Self {
+            record: watch::Sender::default(),
+            wake: Notify::new(),
+            changed: Notify::new(),
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
+        }
+    }
+}
 
-    let replica = Arc::new(RwLock::new(Replica::new(storage)));
+impl SyncMonitor {
+    pub fn record(&self) -> SyncRecord {
+        self.record.borrow().clone()
+    }
 
-    let server_config = ServerConfig::Remote {
-        url,
-        client_id,
-        encryption_secret,
-    };
+    /// a commit to `uuids` the server should hear about soon
+    pub fn local_change(&self, uuids: Vec<Uuid>) {
+        self.changed.notify_one();
+        self.announce(uuids);
+    }
+
+    /// tasks changed by local commits or by syncs, from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Vec<Uuid>> {
+        self.changes.subscribe()
+    }
+
+    fn announce(&self, uuids: Vec<Uuid>) {
+        // no one listening is fine
+        if !uuids.is_empty() {
+            let _ = self.changes.send(uuids);
+        }
+    }
+
+    /// runs a sync now instead of at the next tick, and waits up to `timeout`
+    /// for it to finish
+    pub async fn sync_now(&self, timeout: Duration) -> SyncRecord {
+        if self.record.borrow().local_only {
+            return self.record();
+        }
+        let requested = Utc::now();
+        let mut rx = self.record.subscribe();
+        self.wake.notify_one();
+        let done = tokio::time::timeout(
+            timeout,
+            rx.wait_for(|record| {
+                !record.syncing && record.last_attempt.is_some_and(|at| at >= requested)
+            }),
+        )
+        .await;
+        if done.is_err() {
+            info!("sync still running after {timeout:?}");
+        }
+        self.record()
+    }
+}
 
-    Ok((replica, server_config))
+/// exponential in the number of failures in a row, with jitter so replicas
+/// that lost the server together don't come back in lockstep
+fn backoff(failures: u32) -> Duration {
+    let exp = BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(failures.saturating_sub(1)))
+        .min(MAX_BACKOFF);
+    exp.mul_f64(rand::random_range(0.5..=1.0))
 }
 
-pub fn start_sync_loop<S: Storage + Sync + 'static>(replica: ArcRep<S>, config: ServerConfig) {
+/// one thread syncing every replica, each on its own schedule. Returns once
+/// `shutdown` is cancelled and any sync in flight is done.
+pub fn start_sync_loop(
+    replicas: Arc<Replicas>,
+    shutdown: CancellationToken,
+) -> std::thread::JoinHandle<()> {
+    let mut added = replicas
+        .take_added()
+        .expect("only one sync loop runs at a time");
     std::thread::spawn(move || {
         let rt = tokio::runtime::Builder::new_current_thread()
             .enable_all()
             .build()
             .unwrap();
 
-        rt.block_on(async move {
-            let mut server = config
-                .into_server()
-                .await
-                .inspect_err(|err| {
-                    info!("server err: {err:?}");
-                })
-                .unwrap();
-            info!("sync loop setup");
+        // servers aren't Send, every replica's loop stays on this thread
+        let local = tokio::task::LocalSet::new();
+        local.block_on(&rt, async move {
+            let mut loops = tokio::task::JoinSet::new();
             loop {
-                replica
-                    .write()
-                    .await
-                    .sync(&mut server, false)
-                    .await
-                    .inspect_err(|err| {
-                        info!("lock err: {err:?}");
-                    })
-                    .ok();
-                tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    Some(tenant) = added.recv() => {
+                        loops.spawn_local(sync_replica(tenant));
+                    }
+                }
             }
+            while loops.join_next().await.is_some() {}
+            info!("sync loop stopped");
         });
-    });
+    })
+}
+
+/// syncs after local changes, on request, and every `PULL_INTERVAL` otherwise,
+/// until the tenant is stopped
+async fn sync_replica(tenant: Tenant) {
+    let Tenant {
+        replica,
+        monitor,
+        backend,
+        stop,
+        ..
+    } = tenant;
+    info!("sync loop setup for {backend}");
+    let mut server = match backend.into_server().await {
+        Ok(Some(server)) => server,
+        Ok(None) => {
+            info!("no sync backend, running local only");
+            monitor.record.send_modify(|record| record.local_only = true);
+            return;
+        }
+        Err(err) => {
+            info!("server err: {err:?}");
+            monitor.record.send_modify(|record| {
+                record.last_error = Some(err.to_string());
+            });
+            return;
+        }
+    };
+    let mut failures = 0;
+    'sync: loop {
+        monitor.record.send_modify(|record| {
+            record.syncing = true;
+            record.last_attempt = Some(Utc::now());
+            record.retry_at = None;
+        });
+        let res = sync_changes(&mut *replica.write().await, &mut server).await;
+        let wait = match &res {
+            Ok(uuids) => {
+                monitor.announce(uuids.clone());
+                failures = 0;
+                PULL_INTERVAL
+            }
+            Err(err) => {
+                failures += 1;
+                info!("sync err ({failures} in a row): {err:?}");
+                backoff(failures)
+            }
+        };
+        monitor.record.send_modify(|record| {
+            record.syncing = false;
+            match &res {
+                Ok(_) => {
+                    record.last_success = record.last_attempt;
+                    record.last_error = None;
+                }
+                Err(err) => {
+                    record.last_error = Some(err.to_string());
+                    record.retry_at = chrono::Duration::from_std(wait)
+                        .ok()
+                        .map(|wait| Utc::now() + wait);
+                }
+            }
+        });
+
+        tokio::select! {
+            _ = stop.cancelled() => break 'sync,
+            _ = tokio::time::sleep(wait) => {}
+            _ = monitor.wake.notified() => {}
+            // while backing off only a manual sync cuts the wait short
+            _ = monitor.changed.notified(), if failures == 0 => {
+                if !settle(&monitor, &stop, DEBOUNCE, MAX_DEBOUNCE).await {
+                    break 'sync;
+                }
+            }
+        }
+    }
+}
+
+/// waits for local changes to settle, `quiet` without a new one but no more than
+/// `max` after the first, a manual sync cuts it short. False once `stop` is cancelled
+async fn settle(
+    monitor: &SyncMonitor,
+    stop: &CancellationToken,
+    quiet: Duration,
+    max: Duration,
+) -> bool {
+    let deadline = tokio::time::sleep(max);
+    tokio::pin!(deadline);
+    loop {
+        tokio::select! {
+            _ = stop.cancelled() => return false,
+            _ = &mut deadline => return true,
+            _ = tokio::time::sleep(quiet) => return true,
+            _ = monitor.wake.notified() => return true,
+            _ = monitor.changed.notified() => {}
+        }
+    }
+}
+
+/// syncs and returns the tasks the server changed, our own operations sent up
+/// leave the task data as it was so they don't show
+async fn sync_changes<S: Storage>(
+    rep: &mut Replica<S>,
+    server: &mut Box<dyn taskchampion::Server>,
+) -> TcResult<Vec<Uuid>> {
+    let before = rep.all_task_data().await?;
+    rep.sync(server, false).await?;
+    let after = rep.all_task_data().await?;
+    Ok(changed_tasks(&before, &after))
+}
+
+fn changed_tasks(
+    before: &HashMap<Uuid, TaskData>,
+    after: &HashMap<Uuid, TaskData>,
+) -> Vec<Uuid> {
+    let gone = before.keys().filter(|uuid| !after.contains_key(uuid));
+    let changed = after
+        .iter()
+        .filter(|(uuid, data)| before.get(uuid) != Some(data))
+        .map(|(uuid, _)| uuid);
+    gone.chain(changed).copied().collect()
 }
 
 type TcResult<T> = std::result::Result<T, taskchampion::Error>;
@@ -118,6 +324,55 @@ impl<'t> Txn<'t> {
             .next_id;
         Ok(next_id as usize)
     }
+
+    /// replace the task's row in the full-text index
+    async fn index_task(&mut self, uuid: Uuid, task: &TaskMap) -> TcResult<()> {
+        let field = |key: &str| task.get(key).map(String::as_str).unwrap_or_default();
+        let status = field("status");
+        let description = field("description");
+        let project = field("project");
+        let annotations = task
+            .iter()
+            .filter(|(key, _)| key.starts_with("annotation_"))
+            .map(|(_, value)| value)
+            .join(" ");
+        let tags = task.keys().filter_map(|key| key.strip_prefix("tag_")).join(" ");
+
+        let tx = self.get_txn()?;
+        let row = query!(
+            r#"
+            INSERT INTO taskdb_search_rows (uuid) VALUES (?)
+            ON CONFLICT (uuid) DO UPDATE SET uuid = excluded.uuid
+            RETURNING id
+            "#,
+            uuid
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(to_tc_err)?
+        .id;
+        query!("DELETE FROM taskdb_search WHERE rowid = ?", row)
+            .execute(&mut **tx)
+            .await
+            .map_err(to_tc_err)?;
+        query!(
+            r#"
+            INSERT INTO taskdb_search (rowid, uuid, status, description, annotations, tags, project)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            row,
+            uuid,
+            status,
+            description,
+            annotations,
+            tags,
+            project,
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(to_tc_err)?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -188,7 +443,7 @@ impl StorageTxn for Txn<'_> {
         .execute(&mut **tx)
         .await
         .map_err(to_tc_err)?;
-        Ok(())
+        self.index_task(uuid, &task).await
     }
 
     /// Delete a task, if it exists.  Returns true if the task was deleted (already existed)
@@ -199,6 +454,20 @@ impl StorageTxn for Txn<'_> {
             .await
             .map_err(to_tc_err)?
             .rows_affected();
+        query!(
+            r#"
+            DELETE FROM taskdb_search
+            WHERE rowid = (SELECT id FROM taskdb_search_rows WHERE uuid = ?)
+            "#,
+            uuid
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(to_tc_err)?;
+        query!("DELETE FROM taskdb_search_rows WHERE uuid = ?", uuid)
+            .execute(&mut **tx)
+            .await
+            .map_err(to_tc_err)?;
         Ok(rows_affected > 0)
     }
 
@@ -464,6 +733,8 @@ fn to_tc_err<E: Display>(err: E) -> TcError {
 }
 
 #[cfg(test)]
+// the working set tests sort like this from before the lint existed
+#[allow(clippy::unnecessary_sort_by)]
 mod tests {
     use super::*;
     use taskchampion::chrono::Utc;
@@ -675,6 +946,74 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn set_and_delete_task_update_search_index() {
+        let mut storage = setup_storage().await;
+        let uuid = Uuid::new_v4();
+
+        {
+            let mut txn = storage.txn().await.unwrap();
+            txn.set_task(
+                uuid,
+                taskmap_with(vec![
+                    ("status".to_string(), "pending".to_string()),
+                    ("description".to_string(), "repot the ferns".to_string()),
+                    ("annotation_1700000000".to_string(), "buy compost".to_string()),
+                    ("tag_garden".to_string(), "".to_string()),
+                    ("project".to_string(), "home".to_string()),
+                ]),
+            )
+            .await
+            .unwrap();
+            txn.commit().await.unwrap();
+        }
+
+        let pool = storage.conn.clone();
+        let matches = |term: &'static str| {
+            let pool = pool.clone();
+            async move {
+                query!(
+                    "SELECT count(*) as count FROM taskdb_search WHERE taskdb_search MATCH ?",
+                    term
+                )
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+                .count
+            }
+        };
+        assert_eq!(matches("fern").await, 1);
+        assert_eq!(matches("compost").await, 1);
+        assert_eq!(matches("tags:garden").await, 1);
+        assert_eq!(matches("project:home").await, 1);
+        assert_eq!(matches("office").await, 0);
+
+        // a change replaces the task's row rather than adding another
+        {
+            let mut txn = storage.txn().await.unwrap();
+            txn.set_task(
+                uuid,
+                taskmap_with(vec![
+                    ("status".to_string(), "pending".to_string()),
+                    ("description".to_string(), "repot the office ferns".to_string()),
+                ]),
+            )
+            .await
+            .unwrap();
+            txn.commit().await.unwrap();
+        }
+        assert_eq!(matches("fern").await, 1);
+        assert_eq!(matches("office").await, 1);
+        assert_eq!(matches("compost").await, 0);
+
+        {
+            let mut txn = storage.txn().await.unwrap();
+            assert!(txn.delete_task(uuid).await.unwrap());
+            txn.commit().await.unwrap();
+        }
+        assert_eq!(matches("fern").await, 0);
+    }
+
     #[tokio::test]
     async fn delete_task_missing() {
         let mut storage = setup_storage().await;
@@ -1132,4 +1471,102 @@ mod tests {
             assert_eq!(txn.get_task_operations(uuid2).await.unwrap().len(), 0);
         }
     }
+
+    #[tokio::test]
+    async fn sync_now_wakes_the_loop_and_waits_for_it() {
+        let monitor = Arc::new(SyncMonitor::default());
+        let looping = monitor.clone();
+        tokio::spawn(async move {
+            looping.wake.notified().await;
+            looping.record.send_modify(|record| {
+                record.last_attempt = Some(Utc::now());
+                record.last_error = Some("offline".to_owned());
+            });
+        });
+
+        let record = monitor.sync_now(Duration::from_secs(5)).await;
+        assert!(record.last_attempt.is_some());
+        assert_eq!(record.last_error.as_deref(), Some("offline"));
+
+        // nothing listening, gives up and reports what it has
+        let record = monitor.sync_now(Duration::from_millis(10)).await;
+        assert_eq!(record.last_error.as_deref(), Some("offline"));
+    }
+
+    #[tokio::test]
+    async fn steady_changes_settle_by_the_cap() {
+        let monitor = Arc::new(SyncMonitor::default());
+        let stop = CancellationToken::new();
+        let busy = {
+            let monitor = monitor.clone();
+            tokio::spawn(async move {
+                loop {
+                    monitor.local_change(vec![]);
+                    tokio::time::sleep(Duration::from_millis(10)).await;
+                }
+            })
+        };
+
+        let start = std::time::Instant::now();
+        let quiet = Duration::from_millis(50);
+        let max = Duration::from_millis(200);
+        assert!(settle(&monitor, &stop, quiet, max).await);
+        let waited = start.elapsed();
+        assert!(waited >= max && waited < max * 3, "{waited:?}");
+        busy.abort();
+
+        stop.cancel();
+        assert!(!settle(&monitor, &stop, quiet, max).await);
+    }
+
+    #[test]
+    fn backoff_doubles_with_jitter_up_to_the_cap() {
+        for failures in 1..20 {
+            let full = BASE_BACKOFF
+                .saturating_mul(2u32.saturating_pow(failures - 1))
+                .min(MAX_BACKOFF);
+            let wait = backoff(failures);
+            assert!(wait >= full / 2 && wait <= full, "{failures}: {wait:?}");
+        }
+        assert!(backoff(30) <= MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn changes_reach_subscribers_and_diff_task_data() {
+        let monitor = SyncMonitor::default();
+        let mut changes = monitor.subscribe();
+        let uuid = Uuid::new_v4();
+        monitor.local_change(vec![uuid]);
+        // an empty commit isn't worth a message
+        monitor.local_change(vec![]);
+        monitor.announce(vec![]);
+        assert_eq!(changes.recv().await.unwrap(), vec![uuid]);
+        assert!(changes.try_recv().is_err());
+
+        let mut rep = Replica::new(taskchampion::storage::inmemory::InMemoryStorage::new());
+        let mut ops = taskchampion::Operations::new();
+        let mut uuids = vec![];
+        for description in ["kept", "edited", "gone"] {
+            let mut task = rep.create_task(Uuid::new_v4(), &mut ops).await.unwrap();
+            task.set_description(description.into(), &mut ops).unwrap();
+            uuids.push(task.get_uuid());
+        }
+        rep.commit_operations(ops).await.unwrap();
+        let [kept, edited, gone] = uuids[..] else {
+            unreachable!()
+        };
+        let before = rep.all_task_data().await.unwrap();
+
+        let mut ops = taskchampion::Operations::new();
+        let mut task = rep.get_task(edited).await.unwrap().unwrap();
+        task.set_description("edited twice".into(), &mut ops).unwrap();
+        rep.commit_operations(ops).await.unwrap();
+        let mut after = rep.all_task_data().await.unwrap();
+        after.remove(&gone);
+
+        let changed = changed_tasks(&before, &after);
+        assert_eq!(changed.len(), 2);
+        assert!(changed.contains(&edited) && changed.contains(&gone));
+        assert!(!changed.contains(&kept));
+    }
 }
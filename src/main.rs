@@ -8,6 +8,7 @@ use crate::app::drivers;
 use crate::core::services::{self, CreateServiceParams};
 use crate::infra::axum::start_server;
 use crate::infra::sqlx::{create_sqlx, run_migration};
+use crate::infra::replicas::Replicas;
 use crate::infra::task::start_sync_loop;
 use crate::infra::tower_session::create_session_store;
 use axum::Router;
@@ -30,13 +31,31 @@ async fn main() -> anyhow::Result<()> {
     let pool = create_sqlx();
     let session_store = create_session_store(&pool);
     let webauthn = infra::webauthn::create_authn();
-    let (task_replica, task_server_config) = infra::task::create_task_storage(&pool).await?;
-    let (user_repo, auth_repo, task_repo) = driven::create_driven(&pool, task_replica.clone());
-    let (user_service, task_service, auth_service) =
+    let replicas = Replicas::new(&pool, shutdown_token.clone())?;
+    let driven::Repos {
+        user_repo,
+        auth_repo,
+        task_repo,
+        report_repo,
+        settings_repo,
+        search_repo,
+        uda_repo,
+        feed_repo,
+        sync_repo,
+        account_repo,
+    } = driven::create_driven(&pool, replicas.clone());
+    let (user_service, task_service, auth_service, report_service, settings_service, sync_service) =
         services::create_services(CreateServiceParams {
             user_repo,
             auth_repo,
             task_repo,
+            report_repo,
+            settings_repo,
+            search_repo,
+            uda_repo,
+            feed_repo,
+            sync_repo,
+            account_repo,
             webauthn,
         });
 
@@ -46,15 +65,27 @@ async fn main() -> anyhow::Result<()> {
         app,
         #[cfg(debug_assertions)]
         rx,
-        #[cfg(debug_assertions)]
         shutdown_token: shutdown_token.clone(),
         user_service,
         auth_service,
-        task_service,
+        task_service: task_service.clone(),
+        report_service,
+        settings_service,
+        sync_service: sync_service.clone(),
     });
 
     run_migration(&pool).await?;
-    start_sync_loop(task_replica, task_server_config);
+    match sync_service.open_accounts().await {
+        Ok(0) => {}
+        Ok(opened) => info!("opened {opened} replicas of users with their own task account"),
+        Err(err) => info!("could not open task accounts: {err:?}"),
+    }
+    let sync_loop = start_sync_loop(replicas, shutdown_token.clone());
+    drivers::recurrence::start_recurrence_loop(task_service, shutdown_token.clone());
     start_server(app, tx, shutdown_token, session_store).await;
+    // let a sync in flight finish so its changes aren't sent twice
+    if sync_loop.join().is_err() {
+        info!("sync loop panicked");
+    }
     Ok(())
 }